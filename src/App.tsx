@@ -8,11 +8,15 @@ export const App = () => {
     grid,
     setWidth,
     setHeight,
+    options,
+    updateOptions,
     solution,
     currentStep,
     currentDisplayGrid,
     highlightedCells,
     totalCleared,
+    isDeadBoard,
+    error,
     isSolving,
     initializeGrid,
     updateCell,
@@ -42,6 +46,8 @@ export const App = () => {
           height={height}
           onWidthChange={setWidth}
           onHeightChange={setHeight}
+          options={options}
+          onOptionsChange={updateOptions}
           onCreateGrid={initializeGrid}
           onImportGrid={importGrid}
           onExportGrid={exportGrid}
@@ -67,6 +73,9 @@ export const App = () => {
             grid={grid}
             highlightedCells={highlightedCells}
             totalCleared={totalCleared}
+            isDeadBoard={isDeadBoard}
+            error={error}
+            options={options}
             onStepChange={goToStep}
             onPrevStep={prevStep}
             onNextStep={nextStep}
@@ -1,10 +1,13 @@
 import { useState } from 'react'
+import type { RectangleMode, SolverOptions } from '../types'
 
 interface GridConfigProps {
   width: number
   height: number
   onWidthChange: (width: number) => void
   onHeightChange: (height: number) => void
+  options: SolverOptions
+  onOptionsChange: (patch: Partial<SolverOptions>) => void
   onCreateGrid: () => void
   onImportGrid: (grid: number[][]) => boolean
   onExportGrid: () => string
@@ -15,6 +18,8 @@ export const GridConfig = ({
   height,
   onWidthChange,
   onHeightChange,
+  options,
+  onOptionsChange,
   onCreateGrid,
   onImportGrid,
   onExportGrid,
@@ -85,6 +90,17 @@ export const GridConfig = ({
             className="w-24 px-4 py-2 bg-slate-50 border border-slate-300 rounded-lg text-slate-800 focus:outline-none focus:border-orange-500 focus:ring-2 focus:ring-orange-500/20 transition-colors"
           />
         </div>
+        <div>
+          <label className="block text-sm text-slate-600 mb-2">Rectangles</label>
+          <select
+            value={options.rectangleMode ?? 'sparse'}
+            onChange={(e) => onOptionsChange({ rectangleMode: e.target.value as RectangleMode })}
+            className="px-4 py-2 bg-slate-50 border border-slate-300 rounded-lg text-slate-800 focus:outline-none focus:border-orange-500 focus:ring-2 focus:ring-orange-500/20 transition-colors"
+          >
+            <option value="sparse">Skip empty cells</option>
+            <option value="solid">Solid only</option>
+          </select>
+        </div>
         <button
           onClick={onCreateGrid}
           className="px-6 py-2 bg-indigo-600 hover:bg-indigo-700 text-white rounded-lg font-medium transition-colors shadow-sm"
@@ -1,4 +1,4 @@
-import type { Step } from '../types'
+import type { SolverOptions, Step } from '../types'
 import { MiniGrid } from './MiniGrid'
 import { countRemainingBlocks, getSelectionType } from '../lib/utils'
 
@@ -8,17 +8,32 @@ interface SolutionDisplayProps {
   grid: number[][]
   highlightedCells: Set<string>
   totalCleared: number
+  isDeadBoard: boolean
+  error: string | null
+  options: SolverOptions
   onStepChange: (step: number) => void
   onPrevStep: () => void
   onNextStep: () => void
 }
 
+// Describe the sums a combination has to reach, naming each shape's target when they differ
+const describeTargets = (options: SolverOptions): string => {
+  const { horizontalTarget = 10, verticalTarget = 10, rectangleTarget = 10 } = options
+  if (horizontalTarget === verticalTarget && verticalTarget === rectangleTarget) {
+    return `make ${horizontalTarget}`
+  }
+  return `make ${horizontalTarget} in a row, ${verticalTarget} in a column or ${rectangleTarget} in a rectangle`
+}
+
 export const SolutionDisplay = ({
   solution,
   currentStep,
   grid,
   highlightedCells,
   totalCleared,
+  isDeadBoard,
+  error,
+  options,
   onStepChange,
   onPrevStep,
   onNextStep,
@@ -31,8 +46,24 @@ export const SolutionDisplay = ({
           Solution Steps
         </h2>
         <div className="text-center py-8">
-          <p className="text-xl text-red-500">❌ No valid moves found!</p>
-          <p className="text-slate-500 mt-2">Try different numbers or check your input.</p>
+          {error ? (
+            <>
+              <p className="text-xl text-red-500">⚠️ The solver failed</p>
+              <p className="text-slate-500 mt-2">{error}</p>
+            </>
+          ) : isDeadBoard ? (
+            <>
+              <p className="text-xl text-red-500">❌ No valid moves found!</p>
+              <p className="text-slate-500 mt-2">
+                {countRemainingBlocks(grid)} blocks remain, but none of them can be combined to {describeTargets(options)}.
+              </p>
+            </>
+          ) : (
+            <>
+              <p className="text-xl text-slate-500">The grid is empty</p>
+              <p className="text-slate-500 mt-2">Fill in some block values and try again.</p>
+            </>
+          )}
         </div>
       </div>
     )
@@ -123,32 +154,40 @@ export const SolutionDisplay = ({
                 </span>
               </div>
               
-              {/* Position indicator */}
-              {(() => {
-                const cells = currentStepData.cells
-                const rows = [...new Set(cells.map(c => c.row + 1))].sort((a, b) => a - b)
-                const cols = [...new Set(cells.map(c => c.col + 1))].sort((a, b) => a - b)
-                return (
-                  <p className="text-sm text-slate-600 mb-3">
-                    📍 Row{rows.length > 1 ? 's' : ''} <strong className="text-slate-800">{rows.join('-')}</strong>, 
-                    Col{cols.length > 1 ? 's' : ''} <strong className="text-slate-800">{cols.join('-')}</strong>
-                  </p>
-                )
-              })()}
+              {selectionType === 'Pass' ? (
+                <p className="text-sm text-slate-600">
+                  ⏸ No remaining move is worth taking, so this turn is passed.
+                </p>
+              ) : (
+                <>
+                  {/* Position indicator */}
+                  {(() => {
+                    const cells = currentStepData.cells
+                    const rows = [...new Set(cells.map(c => c.row + 1))].sort((a, b) => a - b)
+                    const cols = [...new Set(cells.map(c => c.col + 1))].sort((a, b) => a - b)
+                    return (
+                      <p className="text-sm text-slate-600 mb-3">
+                        📍 Row{rows.length > 1 ? 's' : ''} <strong className="text-slate-800">{rows.join('-')}</strong>, 
+                        Col{cols.length > 1 ? 's' : ''} <strong className="text-slate-800">{cols.join('-')}</strong>
+                      </p>
+                    )
+                  })()}
               
-              <div className="flex flex-wrap gap-2 mb-3">
-                {currentStepData.cells.map((cell, idx) => (
-                  <span 
-                    key={idx}
-                    className="px-2 py-1 bg-orange-100 border border-orange-200 rounded-lg text-orange-700 font-mono text-sm"
-                  >
-                    R{cell.row + 1}C{cell.col + 1}: <strong>{cell.value}</strong>
-                  </span>
-                ))}
-              </div>
-              <p className="text-slate-600 text-sm">
-                Sum: {currentStepData.cells.map(c => c.value).join(' + ')} = <strong className="text-orange-600">10</strong>
-              </p>
+                  <div className="flex flex-wrap gap-2 mb-3">
+                    {currentStepData.cells.map((cell, idx) => (
+                      <span 
+                        key={idx}
+                        className="px-2 py-1 bg-orange-100 border border-orange-200 rounded-lg text-orange-700 font-mono text-sm"
+                      >
+                        R{cell.row + 1}C{cell.col + 1}: <strong>{cell.value}</strong>
+                      </span>
+                    ))}
+                  </div>
+                  <p className="text-slate-600 text-sm">
+                    Sum: {currentStepData.cells.map(c => c.value).join(' + ')} = <strong className="text-orange-600">{currentStepData.sum}</strong>
+                  </p>
+                </>
+              )}
             </div>
           </div>
         </div>
@@ -1,6 +1,6 @@
 import { useState, useCallback, useMemo, useEffect, useRef } from 'react'
-import type { Step } from '../types'
-import { createEmptyGrid } from '../lib/utils'
+import type { SolverOptions, Step } from '../types'
+import { createEmptyGrid, validateGrid } from '../lib/utils'
 
 const DEFAULT_WIDTH = 8
 const DEFAULT_HEIGHT = 14
@@ -8,11 +8,13 @@ const DEFAULT_HEIGHT = 14
 interface WorkerMessage {
   type: 'solve'
   grid: number[][]
+  options?: SolverOptions
 }
 
 interface WorkerResponse {
   type: 'result' | 'error'
   steps?: Step[]
+  isDeadBoard?: boolean
   error?: string
 }
 
@@ -25,6 +27,9 @@ export const useSolver = () => {
   const [solution, setSolution] = useState<Step[] | null>(null)
   const [currentStep, setCurrentStep] = useState(0)
   const [isSolving, setIsSolving] = useState(false)
+  const [isDeadBoard, setIsDeadBoard] = useState(false)
+  const [error, setError] = useState<string | null>(null)
+  const [options, setOptions] = useState<SolverOptions>({})
   
   const workerRef = useRef<Worker | null>(null)
 
@@ -40,17 +45,24 @@ export const useSolver = () => {
       
       if (type === 'result' && event.data.steps) {
         setSolution(event.data.steps)
+        setIsDeadBoard(event.data.isDeadBoard ?? false)
+        setError(null)
         setCurrentStep(0)
         setIsSolving(false)
       } else if (type === 'error') {
         console.error('Solver error:', event.data.error)
         setSolution([])
+        setIsDeadBoard(false)
+        setError(event.data.error ?? 'Unknown error')
         setIsSolving(false)
       }
     }
     
     worker.onerror = (error) => {
       console.error('Worker error:', error)
+      setSolution([])
+      setIsDeadBoard(false)
+      setError(error.message || 'The solver stopped unexpectedly')
       setIsSolving(false)
     }
     
@@ -73,19 +85,12 @@ export const useSolver = () => {
       return false
     }
     
+    // Validate that all rows have the same length and values are 0-9
+    if (validateGrid(newGrid)) return false
+    
     const newHeight = newGrid.length
     const newWidth = newGrid[0].length
     
-    // Validate that all rows have the same length and values are 0-9
-    for (const row of newGrid) {
-      if (row.length !== newWidth) return false
-      for (const cell of row) {
-        if (typeof cell !== 'number' || cell < 0 || cell > 9 || !Number.isInteger(cell)) {
-          return false
-        }
-      }
-    }
-    
     setWidth(newWidth)
     setHeight(newHeight)
     setGrid(newGrid.map(row => [...row]))
@@ -113,9 +118,16 @@ export const useSolver = () => {
     setIsSolving(true)
     workerRef.current.postMessage({
       type: 'solve',
-      grid
+      grid,
+      options
     } satisfies WorkerMessage)
-  }, [grid, isSolving])
+  }, [grid, options, isSolving])
+
+  const updateOptions = useCallback((patch: Partial<SolverOptions>) => {
+    setOptions(prev => ({ ...prev, ...patch }))
+    setSolution(null)
+    setCurrentStep(0)
+  }, [])
 
   const reset = useCallback(() => {
     setSolution(null)
@@ -159,12 +171,18 @@ export const useSolver = () => {
     setWidth,
     setHeight,
     
+    // Solver options
+    options,
+    updateOptions,
+    
     // Solution state
     solution,
     currentStep,
     currentDisplayGrid,
     highlightedCells,
     totalCleared,
+    isDeadBoard,
+    error,
     
     // Loading state
     isSolving,
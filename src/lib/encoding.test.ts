@@ -0,0 +1,167 @@
+import { describe, expect, it } from 'vitest'
+import type { SolverOptions } from '../types'
+import {
+  createSolveRecipe,
+  fromColumnarLog,
+  fromDeltaEncodedMoves,
+  fromKeyframedSteps,
+  gridFromBase64,
+  gridToBase64,
+  packNibbleGrid,
+  replaySolveRecipe,
+  toColumnarLog,
+  toDeltaEncodedMoves,
+  toKeyframedSteps,
+  toTrainingSamples,
+  unpackNibbleGrid,
+} from './encoding'
+import { getLegalMoves, solvePuzzle, startGame } from './solver'
+
+const grid = [
+  [1, 9, 0, 4, 6],
+  [2, 8, 5, 5, 0],
+  [3, 7, 1, 2, 7],
+]
+
+describe('base64 grids', () => {
+  const roundTrip = (board: number[][]) => {
+    const encoded = gridToBase64(board)
+    return encoded === null ? null : gridFromBase64(encoded)
+  }
+
+  it('round-trips a board', () => {
+    expect(roundTrip(grid)).toEqual(grid)
+  })
+
+  it('round-trips an odd number of cells', () => {
+    const odd = [[1, 2, 3], [4, 5, 6], [7, 8, 9]]
+    expect(roundTrip(odd)).toEqual(odd)
+  })
+
+  it('round-trips an empty board', () => {
+    expect(roundTrip([])).toEqual([])
+  })
+
+  it('refuses boards the format cannot hold', () => {
+    expect(gridToBase64([[1, -1, 9]])).toBeNull()
+    expect(gridToBase64([[1, 12, 9]])).toBeNull()
+    expect(gridToBase64([[1, 2], [3]])).toBeNull()
+    expect(gridToBase64([Array.from({ length: 256 }, () => 1)])).toBeNull()
+    expect(roundTrip([Array.from({ length: 255 }, () => 1)])).toEqual([Array.from({ length: 255 }, () => 1)])
+  })
+
+  it('rejects malformed input', () => {
+    expect(gridFromBase64('!!!')).toBeNull()
+    expect(gridFromBase64('')).toBeNull()
+  })
+})
+
+describe('nibble packing', () => {
+  it('round-trips a board', () => {
+    expect(unpackNibbleGrid(packNibbleGrid(grid), 3, 5)).toEqual(grid)
+  })
+
+  it('rejects data that does not fit the dimensions', () => {
+    expect(unpackNibbleGrid(packNibbleGrid(grid), 4, 5)).toBeNull()
+  })
+})
+
+describe('solution formats', () => {
+  const steps = solvePuzzle(grid)
+
+  it('round-trips a columnar log', () => {
+    expect(fromColumnarLog(toColumnarLog(steps), grid)).toEqual(steps)
+  })
+
+  it('round-trips keyframed steps', () => {
+    expect(fromKeyframedSteps(toKeyframedSteps(steps, 2))).toEqual(steps)
+  })
+
+  it('round-trips delta-encoded moves', () => {
+    const decoded = fromDeltaEncodedMoves(toDeltaEncodedMoves(steps), grid)
+    expect(decoded?.map(step => step.gridAfter)).toEqual(steps.map(step => step.gridAfter))
+    expect(decoded?.map(step => step.score)).toEqual(steps.map(step => step.score))
+  })
+
+  it('encodes long contiguous clears smaller than absolute positions', () => {
+    const board = Array.from({ length: 12 }, (_, r) => {
+      return Array.from({ length: 12 }, (_, c) => (r === 11 && c >= 2 ? 1 : 0))
+    })
+    const lineSteps = solvePuzzle(board)
+    const absolute = lineSteps.map(step => step.cells.flatMap(c => [c.row, c.col]))
+    const encoded = toDeltaEncodedMoves(lineSteps)
+    expect(lineSteps[0].cells).toHaveLength(10)
+    expect(JSON.stringify(encoded).length).toBeLessThan(JSON.stringify(absolute).length)
+    expect(fromDeltaEncodedMoves(encoded, board)?.map(step => step.gridAfter))
+      .toEqual(lineSteps.map(step => step.gridAfter))
+  })
+
+  it('replays refills when decoding', () => {
+    const options = { gravity: 'down' as const, refillQueue: [5, 5, 1, 9, 3, 7] }
+    const refillSteps = solvePuzzle(grid, options)
+    const decoded = fromDeltaEncodedMoves(toDeltaEncodedMoves(refillSteps), grid, options)
+    expect(decoded?.map(step => step.gridAfter)).toEqual(refillSteps.map(step => step.gridAfter))
+  })
+
+  it('round-trips one-indexed moves', () => {
+    const oneIndexedSteps = solvePuzzle(grid, { oneIndexed: true })
+    const decoded = fromDeltaEncodedMoves(toDeltaEncodedMoves(oneIndexedSteps), grid, { oneIndexed: true })
+    expect(decoded?.map(step => step.gridAfter)).toEqual(oneIndexedSteps.map(step => step.gridAfter))
+  })
+
+  it('rejects delta-encoded moves that leave the board or break the rules', () => {
+    expect(fromDeltaEncodedMoves([[0, 4, 0, 1]], grid)).toBeNull()
+    expect(fromDeltaEncodedMoves([[2, 0, 1, 0]], grid)).toBeNull()
+    expect(fromDeltaEncodedMoves([[0, 0, -1, 0]], grid)).toBeNull()
+    expect(fromDeltaEncodedMoves([[0, 0, 0]], grid)).toBeNull()
+    expect(fromDeltaEncodedMoves([[0, 0, 1, 0]], grid)).toBeNull()
+  })
+
+  it('replays a recipe to the same solution', () => {
+    const options = { tieBreakSeed: 7 }
+    const recipe = createSolveRecipe(grid, options)
+    expect(recipe).not.toBeNull()
+    expect(recipe && replaySolveRecipe(recipe)).toEqual(solvePuzzle(grid, options))
+  })
+
+  it('replays a recipe with unbounded limits', () => {
+    const options = { maxTotalCleared: Infinity, minMoveScore: -Infinity }
+    const recipe = createSolveRecipe(grid, options)
+    expect(recipe?.options).toEqual({})
+    expect(recipe && replaySolveRecipe(recipe)).toEqual(solvePuzzle(grid, options))
+  })
+
+  it('refuses options it cannot serialize', () => {
+    expect(createSolveRecipe(grid, { scoreMove: cells => cells.length })).toBeNull()
+  })
+})
+
+describe('toTrainingSamples', () => {
+  const cases: [string, SolverOptions][] = [
+    ['default rules', {}],
+    ['refills', { gravity: 'down', refillQueue: [5, 5, 1, 9, 3, 7] }],
+    ['one-indexed moves', { oneIndexed: true }],
+  ]
+
+  it.each(cases)('pairs every step with the board it was played on under %s', (_, options) => {
+    const steps = solvePuzzle(grid, options)
+    const samples = toTrainingSamples(grid, steps, options)
+    const boards = [grid, ...steps.map(step => step.gridAfter)].slice(0, steps.length)
+
+    expect(samples).toHaveLength(steps.length)
+    expect(samples?.map(sample => sample.board)).toEqual(boards.map(board => board.flat()))
+    expect(samples?.every(sample => sample.moveIndex >= 0)).toBe(true)
+  })
+
+  it('points at the chosen move among the legal ones', () => {
+    const steps = solvePuzzle(grid)
+    const [first] = toTrainingSamples(grid, steps) ?? []
+    const chosen = getLegalMoves(startGame(grid), {})[first.moveIndex]
+    expect(chosen.map(c => [c.row, c.col]).sort()).toEqual(steps[0].cells.map(c => [c.row, c.col]).sort())
+  })
+
+  it('rejects steps that are not legal moves', () => {
+    const cells = [{ row: 0, col: 0, value: 1 }, { row: 1, col: 0, value: 2 }]
+    expect(toTrainingSamples(grid, [{ cells, sum: 3, score: 2, gridAfter: grid }])).toBeNull()
+  })
+})
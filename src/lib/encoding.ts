@@ -0,0 +1,330 @@
+import type {
+  AnimationFrame,
+  Cell,
+  ColumnarLog,
+  Combination,
+  CompactMove,
+  KeyframedStep,
+  SolveRecipe,
+  SolverOptions,
+  Step,
+  TrainingSample,
+} from '../types'
+import {
+  applyMove,
+  calculateChainedMoveScore,
+  calculateMoveScore,
+  getLegalMoves,
+  playMove,
+  solvePuzzle,
+  startGame,
+  toZeroIndexedOptions,
+} from './solver'
+import {
+  getBoundingBox,
+  getCombinationPositions,
+  getCombinationSum,
+  getSelectionType,
+  shiftSteps,
+  validateGrid,
+} from './utils'
+
+// Pack grid values as 4-bit nibbles in row-major order, two cells per byte (high nibble first)
+export const packNibbleGrid = (grid: number[][]): Uint8Array => {
+  const values = grid.flat()
+  const bytes = new Uint8Array(Math.ceil(values.length / 2))
+  values.forEach((value, idx) => {
+    const nibble = value & 0x0f
+    bytes[idx >> 1] |= idx % 2 === 0 ? nibble << 4 : nibble
+  })
+  return bytes
+}
+
+// Unpack a rows x cols grid from packNibbleGrid data, returning null if the length doesn't match
+export const unpackNibbleGrid = (data: Uint8Array, rows: number, cols: number): number[][] | null => {
+  if (data.length !== Math.ceil((rows * cols) / 2)) return null
+
+  const grid: number[][] = []
+  for (let row = 0; row < rows; row++) {
+    const values: number[] = []
+    for (let col = 0; col < cols; col++) {
+      const idx = row * cols + col
+      const byte = data[idx >> 1]
+      values.push(idx % 2 === 0 ? byte >> 4 : byte & 0x0f)
+    }
+    grid.push(values)
+  }
+
+  return grid
+}
+
+// Solve a board received as packed nibbles, returning null if the data doesn't fit the dimensions
+export const solvePuzzlePacked = (
+  data: Uint8Array,
+  rows: number,
+  cols: number,
+  options: SolverOptions = {}
+): Step[] | null => {
+  const grid = unpackNibbleGrid(data, rows, cols)
+  return grid ? solvePuzzle(grid, options) : null
+}
+
+// Largest height or width the one-byte header of gridToBase64 can hold
+const MAX_BASE64_DIMENSION = 255
+
+// Pack a grid into a URL-safe base64 string: [height, width, ...values as 4-bit nibbles].
+// Returns null for a grid the format can't hold: malformed, hidden cells (which would pack as 15),
+// or more than 255 rows or columns.
+export const gridToBase64 = (grid: number[][]): string | null => {
+  if (validateGrid(grid)) return null
+  if (grid.length > MAX_BASE64_DIMENSION || (grid[0]?.length ?? 0) > MAX_BASE64_DIMENSION) return null
+
+  const height = grid.length
+  const width = grid[0]?.length || 0
+  const bytes = new Uint8Array([height, width, ...packNibbleGrid(grid)])
+
+  const binary = String.fromCharCode(...bytes)
+  return btoa(binary).replace(/\+/g, '-').replace(/\//g, '_').replace(/=+$/, '')
+}
+
+// Decode a grid produced by gridToBase64, returning null if the data is malformed
+export const gridFromBase64 = (encoded: string): number[][] | null => {
+  let binary: string
+  try {
+    binary = atob(encoded.replace(/-/g, '+').replace(/_/g, '/'))
+  } catch {
+    return null
+  }
+
+  const bytes = Uint8Array.from(binary, char => char.charCodeAt(0))
+  if (bytes.length < 2) return null
+
+  const height = bytes[0]
+  const width = bytes[1]
+  if (height === 0 || width === 0) return []
+  return unpackNibbleGrid(bytes.subarray(2), height, width)
+}
+
+// Flatten a solution into per-step columns for analytics ingestion
+export const toColumnarLog = (steps: Step[]): ColumnarLog => {
+  const log: ColumnarLog = {
+    startRow: [],
+    startCol: [],
+    size: [],
+    score: [],
+    sum: [],
+    shape: [],
+    cellOffsets: [0],
+    cellRows: [],
+    cellCols: [],
+    cellValues: [],
+  }
+
+  for (const step of steps) {
+    const box = step.cells.length > 0 ? getBoundingBox(step.cells) : null
+    log.startRow.push(box?.minRow ?? -1)
+    log.startCol.push(box?.minCol ?? -1)
+    log.size.push(step.cells.length)
+    log.score.push(step.score)
+    log.sum.push(step.sum)
+    log.shape.push(getSelectionType(step.cells))
+
+    for (const cell of step.cells) {
+      log.cellRows.push(cell.row)
+      log.cellCols.push(cell.col)
+      log.cellValues.push(cell.value)
+    }
+    log.cellOffsets.push(log.cellRows.length)
+  }
+
+  return log
+}
+
+// Rebuild the steps of a columnar log by replaying its moves on the initial grid
+export const fromColumnarLog = (
+  log: ColumnarLog,
+  initialGrid: number[][],
+  options: SolverOptions = {}
+): Step[] => {
+  const steps: Step[] = []
+  let grid = initialGrid.map(row => [...row])
+
+  for (let idx = 0; idx < log.size.length; idx++) {
+    const cells: Cell[] = []
+    for (let cellIdx = log.cellOffsets[idx]; cellIdx < log.cellOffsets[idx + 1]; cellIdx++) {
+      cells.push({ row: log.cellRows[cellIdx], col: log.cellCols[cellIdx], value: log.cellValues[cellIdx] })
+    }
+
+    grid = cells.length > 0 ? applyMove(grid, cells, options) : grid
+    steps.push({ cells, sum: log.sum[idx], score: log.score[idx], gridAfter: grid })
+  }
+
+  return steps
+}
+
+// Keep the full board only on every keyframeEvery-th step (starting with the first one) and store
+// the remaining steps as diffs against the step before them
+export const toKeyframedSteps = (steps: Step[], keyframeEvery: number): KeyframedStep[] => {
+  const interval = Math.max(1, Math.floor(keyframeEvery))
+
+  return steps.map(({ gridAfter, ...step }, idx) => {
+    if (idx % interval === 0) return { ...step, gridAfter }
+
+    const previous = steps[idx - 1].gridAfter
+    const changes: [number, number, number][] = []
+    gridAfter.forEach((row, r) => row.forEach((value, c) => {
+      if (previous[r][c] !== value) changes.push([r, c, value])
+    }))
+    return { ...step, changes }
+  })
+}
+
+// Rebuild the full board of every step by replaying diffs from the most recent keyframe
+export const fromKeyframedSteps = (steps: KeyframedStep[]): Step[] => {
+  const result: Step[] = []
+  let grid: number[][] = []
+
+  for (const { gridAfter, changes = [], ...step } of steps) {
+    grid = (gridAfter ?? grid).map(row => [...row])
+    for (const [row, col, value] of changes) {
+      grid[row][col] = value
+    }
+    result.push({ ...step, gridAfter: grid })
+  }
+
+  return result
+}
+
+// Flatten combinations (e.g. from findValidCombinations) into plain tuples with their shape and score
+export const toCompactMoves = (combinations: Combination[], options: SolverOptions = {}): CompactMove[] => {
+  return combinations.map(cells => ({
+    cells: cells
+      .map((c): [number, number, number] => [c.row, c.col, c.value])
+      .sort((a, b) => a[0] - b[0] || a[1] - b[1]),
+    shape: getSelectionType(cells),
+    score: calculateMoveScore(cells, options),
+  }))
+}
+
+// Turn a solution into an animation script, diffing each board against the one before it
+export const toAnimationFrames = (initialGrid: number[][], steps: Step[]): AnimationFrame[] => {
+  let previous = initialGrid
+
+  return steps.map(step => {
+    const removed: [number, number][] = []
+    step.gridAfter.forEach((row, r) => row.forEach((value, c) => {
+      if (value === 0 && previous[r]?.[c] !== 0) removed.push([r, c])
+    }))
+    previous = step.gridAfter
+
+    return { highlight: step.cells, removed, board: step.gridAfter }
+  })
+}
+
+// Encode each move as [row, col, dRow, dCol, ...]: its first cell followed by the offset of every other cell
+// from the one before, in position order. Contiguous clears turn into runs of small numbers that pack well.
+export const toDeltaEncodedMoves = (steps: Step[]): number[][] => {
+  return steps.map(step => {
+    let previous: [number, number] = [0, 0]
+    return getCombinationPositions(step.cells).flatMap(([row, col]) => {
+      const delta = [row - previous[0], col - previous[1]]
+      previous = [row, col]
+      return delta
+    })
+  })
+}
+
+// Rebuild a solution from delta-encoded moves by replaying them under the solver's rules, so refills and
+// other board changes are reproduced. Returns null if a move has an odd number of values or isn't legal
+// at that point of the game.
+export const fromDeltaEncodedMoves = (
+  moves: number[][],
+  initialGrid: number[][],
+  options: SolverOptions = {}
+): Step[] | null => {
+  const internalOptions = toZeroIndexedOptions(options)
+  const offset = options.oneIndexed ? 1 : 0
+  const toKey = (positions: [number, number][]) => positions.map(([row, col]) => `${row}-${col}`).sort().join('|')
+  let state = startGame(initialGrid, internalOptions)
+  const steps: Step[] = []
+
+  for (const deltas of moves) {
+    if (deltas.length % 2 !== 0) return null
+    if (deltas.length === 0) {
+      steps.push({ cells: [], sum: 0, score: 0, gridAfter: state.grid })
+      continue
+    }
+
+    const positions: [number, number][] = []
+    let row = 0
+    let col = 0
+    for (let idx = 0; idx < deltas.length; idx += 2) {
+      row += deltas[idx]
+      col += deltas[idx + 1]
+      positions.push([row - offset, col - offset])
+    }
+
+    const key = toKey(positions)
+    const cells = getLegalMoves(state, internalOptions).find(move => toKey(getCombinationPositions(move)) === key)
+    if (!cells) return null
+
+    const score = calculateChainedMoveScore(cells, state.chain, internalOptions)
+    state = playMove(state, cells, internalOptions)
+    steps.push({ cells, sum: getCombinationSum(cells), score, gridAfter: state.grid })
+  }
+
+  return options.oneIndexed ? shiftSteps(steps, 1) : steps
+}
+
+// JSON turns Infinity into null, which the solver would read as a limit of 0. Every option limit defaults
+// to unbounded, so non-finite numbers are dropped instead.
+const dropNonFinite = (_key: string, value: unknown) => {
+  return typeof value === 'number' && !Number.isFinite(value) ? undefined : value
+}
+
+// Capture a board and the options used to solve it, for attaching to bug reports. Returns null when the
+// options hold a callback such as scoreMove, which JSON can't carry, or the board can't be encoded,
+// since the recipe couldn't replay the solve.
+export const createSolveRecipe = (grid: number[][], options: SolverOptions = {}): SolveRecipe | null => {
+  if (Object.values(options).some(value => typeof value === 'function')) return null
+
+  const encoded = gridToBase64(grid)
+  return encoded === null ? null : { grid: encoded, options: JSON.parse(JSON.stringify(options, dropNonFinite)) }
+}
+
+// Re-run the solve described by a recipe, returning null if its board can't be decoded
+export const replaySolveRecipe = (recipe: SolveRecipe): Step[] | null => {
+  const grid = gridFromBase64(recipe.grid)
+  return grid ? solvePuzzle(grid, recipe.options) : null
+}
+
+// Export one training sample per step, pairing the board before the step with the index of the chosen move
+// among the legal moves at that point of the game. Returns null if a step isn't a legal move.
+export const toTrainingSamples = (
+  initialGrid: number[][],
+  steps: Step[],
+  options: SolverOptions = {}
+): TrainingSample[] | null => {
+  const internalOptions = toZeroIndexedOptions(options)
+  const moves = options.oneIndexed ? shiftSteps(steps, -1) : steps
+  const toKey = (cells: Cell[]) => getCombinationPositions(cells).join('|')
+  let state = startGame(initialGrid, internalOptions)
+  const samples: TrainingSample[] = []
+
+  for (const step of moves) {
+    if (step.cells.length === 0) {
+      samples.push({ board: state.grid.flat(), moveIndex: -1 })
+      continue
+    }
+
+    const chosenKey = toKey(step.cells)
+    const legalMoves = getLegalMoves(state, internalOptions)
+    const moveIndex = legalMoves.findIndex(cells => toKey(cells) === chosenKey)
+    if (moveIndex === -1) return null
+
+    samples.push({ board: state.grid.flat(), moveIndex })
+    state = playMove(state, legalMoves[moveIndex], internalOptions)
+  }
+
+  return samples
+}
@@ -0,0 +1,193 @@
+import { describe, expect, it } from 'vitest'
+import type { SolverOptions, Step } from '../types'
+import {
+  countOptimalSolutions,
+  findMinMovesFullClear,
+  findParetoFrontier,
+  findPermanentlyStranded,
+  solveAll,
+  solveBestOf,
+  solveExhaustive,
+  solversAgree,
+} from './exhaustive'
+import { solvePuzzle, solveScoreOnly } from './solver'
+import { getRuleCases, randomBoard, totalScore } from './testHelpers'
+import { countRemainingBlocks } from './utils'
+import { verifySolution } from './verify'
+
+describe('exhaustive search plays by the rules', () => {
+  const grid = randomBoard(3, 4, 5)
+  const cases = getRuleCases(grid)
+
+  it.each(cases)('only finds solutions the verifier accepts with %s', (_, options) => {
+    const frontier = findParetoFrontier(grid, options)
+    expect(frontier).not.toBeNull()
+    for (const { score, steps } of frontier ?? []) {
+      expect(verifySolution(grid, steps, options)).toEqual({ valid: true, score })
+    }
+  })
+
+  it.each(cases)('never scores below the greedy solver with %s', (_, options) => {
+    const optimal = countOptimalSolutions(grid, options)
+    expect(optimal).not.toBeNull()
+    expect(optimal?.bestScore).toBeGreaterThanOrEqual(solveScoreOnly(grid, options))
+  })
+})
+
+describe('findPermanentlyStranded', () => {
+  it('keeps searching under gravity after clearing as many cells as there were blocks', () => {
+    expect(findPermanentlyStranded([[6, 0, 4], [7, 3, 4], [3, 0, 1]], { gravity: 'down' })).toEqual([])
+  })
+})
+
+describe('countOptimalSolutions', () => {
+  it('counts each move order reaching the best score', () => {
+    // Either the 1 on the left or the one on the right pairs with the 9, and the other is left over
+    expect(countOptimalSolutions([[1, 9, 1]])).toMatchObject({ bestScore: 2, optimalSolutionCount: 2 })
+  })
+
+  it('counts mirrored move orders once on a symmetric board', () => {
+    const raw = countOptimalSolutions([[1, 9, 1]])
+    const collapsed = countOptimalSolutions([[1, 9, 1]], { collapseSymmetricSolutions: true })
+    expect(collapsed?.optimalSolutionCount).toBe(1)
+    expect(collapsed?.optimalSolutionCount).toBeLessThan(raw?.optimalSolutionCount ?? 0)
+  })
+
+  it('only collapses mirrors the rules respect', () => {
+    // Freezing the top row leaves a single move on a board that is otherwise symmetric top to bottom
+    const grid = [[1, 9], [0, 0], [1, 9]]
+    expect(countOptimalSolutions(grid, { frozenRows: 1, collapseSymmetricSolutions: true }))
+      .toMatchObject({ bestScore: 2, optimalSolutionCount: 1 })
+    expect(countOptimalSolutions(grid, { clearOrder: 'bottomUp', collapseSymmetricSolutions: true }))
+      .toMatchObject({ optimalSolutionCount: 1 })
+    expect(countOptimalSolutions([[1, 9, 1]], { mustClear: [[0, 0]], collapseSymmetricSolutions: true }))
+      .toMatchObject({ optimalSolutionCount: 2 })
+  })
+})
+
+describe('solveExhaustive', () => {
+  // Single blocks of 9 clear for no points, so the best score of 6 is reached by sequences that end with
+  // one block or with two blocks left over
+  const grid = [[1, 8, 8], [3, 6, 6], [1, 9, 0]]
+  const options: SolverOptions = {
+    gravity: 'down',
+    horizontalTarget: 9,
+    verticalTarget: 9,
+    scoreSingleCellClears: false,
+  }
+
+  it('picks the max-score solution that leaves the fewest blocks', () => {
+    const steps = solveExhaustive(grid, options)
+    expect(steps).not.toBeNull()
+    expect(verifySolution(grid, steps ?? [], options)).toEqual({ valid: true, score: 6 })
+    expect(countRemainingBlocks(steps?.[steps.length - 1].gridAfter ?? grid)).toBe(1)
+  })
+
+  it('reports the fewest blocks a max-score solution leaves', () => {
+    expect(countOptimalSolutions(grid, options)).toMatchObject({ bestScore: 6, fewestRemaining: 1 })
+  })
+
+  it('gives up on boards too big to search', () => {
+    expect(solveExhaustive(randomBoard(5, 5, 1), {}, 10)).toBeNull()
+  })
+
+  it('gives up score for the biggest single clear under maxSingleClear', () => {
+    // Five vertical pairs score 10, while clearing the row of 2s at once strands every 8
+    const board = [[2, 2, 2, 2, 2], [8, 8, 8, 8, 8]]
+    const largestClear = (steps: Step[] | null) => Math.max(...(steps ?? []).map(step => step.cells.length))
+    const byScore = solveExhaustive(board)
+    const bySize = solveExhaustive(board, { objective: 'maxSingleClear' })
+
+    expect(totalScore(byScore ?? [])).toBe(10)
+    expect(largestClear(byScore)).toBe(2)
+    expect(totalScore(bySize ?? [])).toBe(5)
+    expect(largestClear(bySize)).toBe(5)
+  })
+})
+
+describe('findMinMovesFullClear', () => {
+  it('clears a square in a single rectangle', () => {
+    expect(findMinMovesFullClear([[1, 4], [2, 3]])).toBe(1)
+  })
+
+  it('finds the only full clear even when the biggest first move rules it out', () => {
+    // Clearing the top row strands the bottom one, so the board only clears column by column
+    const grid = [[1, 2, 7], [9, 8, 3]]
+    expect(findMinMovesFullClear(grid)).toBe(3)
+    expect(countRemainingBlocks(solvePuzzle(grid)[0].gridAfter)).toBe(3)
+  })
+
+  it('returns null when no sequence clears the board', () => {
+    expect(findMinMovesFullClear([[1, 1, 8], [9, 0, 0]])).toBeNull()
+  })
+})
+
+describe('findParetoFrontier', () => {
+  it('keeps both sides of a score against moves trade-off', () => {
+    // The top row clears 5 blocks in one move; pairing the 8s with the 2s above them plays two moves for 4
+    const grid = [[2, 2, 2, 2, 2], [8, 8, 0, 0, 0]]
+    const frontier = findParetoFrontier(grid)
+
+    expect(frontier?.map(({ score, moves }) => ({ score, moves }))).toEqual([
+      { score: 5, moves: 1 },
+      { score: 4, moves: 2 },
+    ])
+    for (const { score, steps } of frontier ?? []) {
+      expect(verifySolution(grid, steps)).toEqual({ valid: true, score })
+    }
+  })
+})
+
+describe('solversAgree', () => {
+  it('agrees when greedy clears the whole board', () => {
+    expect(solversAgree([[1, 9, 5, 5]])).toBe(true)
+  })
+
+  it('disagrees when the biggest first clear costs points later', () => {
+    expect(solversAgree([[2, 4, 1, 5], [9, 1, 4, 8]])).toBe(false)
+  })
+
+  it('gives up on boards too big to search', () => {
+    expect(solversAgree(randomBoard(5, 5, 1), {}, 10)).toBeNull()
+  })
+})
+
+describe('solveBestOf', () => {
+  it('takes the exhaustive solution when it scores higher', () => {
+    const grid = [[2, 4, 1, 5], [9, 1, 4, 8]]
+    expect(solveBestOf(grid)).toEqual({ steps: solveExhaustive(grid), method: 'exhaustive', partialMethods: [] })
+  })
+
+  it('keeps the greedy solution when the exhaustive search finds nothing better', () => {
+    const grid = [[1, 9, 5, 5]]
+    expect(solveBestOf(grid)).toEqual({ steps: solvePuzzle(grid), method: 'greedy', partialMethods: [] })
+  })
+
+  it('falls back to the greedy solution when the exhaustive search gives up', () => {
+    const grid = randomBoard(5, 5, 1)
+    const options: SolverOptions = { reverse: true }
+    expect(solveBestOf(grid, options, 10)).toEqual({
+      steps: solvePuzzle(grid, options),
+      method: 'greedy',
+      partialMethods: ['exhaustive'],
+    })
+  })
+})
+
+describe('solveAll', () => {
+  it('returns a legal, scored solution from every solver', () => {
+    const grid = [[2, 4, 1, 5], [9, 1, 4, 8]]
+    const results = solveAll(grid)
+
+    expect(Object.keys(results).sort()).toEqual(['exhaustive', 'greedy'])
+    for (const result of Object.values(results)) {
+      expect(result).not.toBeNull()
+      expect(verifySolution(grid, result?.steps ?? [])).toEqual({ valid: true, score: result?.score })
+    }
+    expect(results.exhaustive?.score).toBeGreaterThan(results.greedy?.score ?? 0)
+  })
+
+  it('reports the exhaustive search as missing when it gives up', () => {
+    expect(solveAll(randomBoard(5, 5, 1), {}, 10).exhaustive).toBeNull()
+  })
+})
@@ -0,0 +1,454 @@
+import type {
+  BestOfSolution,
+  Cell,
+  Combination,
+  GameState,
+  MethodSolution,
+  ParetoSolution,
+  SolveAllResult,
+  SolveMethod,
+  SolverOptions,
+  Step,
+} from '../types'
+import {
+  calculateChainedMoveScore,
+  getLegalMoves,
+  getRuleSymmetry,
+  meetsMinMoveScore,
+  playMove,
+  solvePuzzle,
+  solveScoreOnly,
+  startGame,
+  toZeroIndexedOptions,
+} from './solver'
+import {
+  countRemainingBlocks,
+  getBoundingBox,
+  getCombinationSum,
+  getGridKey,
+  getSelectionType,
+  reverseSteps,
+  shiftSteps,
+} from './utils'
+import { verifySolution } from './verify'
+
+const DEFAULT_MAX_STATES = 50000
+
+// Identify a game state by its board plus the part of its history the rules still depend on,
+// so two paths only share a memo entry when every continuation is legal for both
+const getStateKey = (state: GameState, options: SolverOptions): string => {
+  const {
+    requireValue,
+    shapeSchedule,
+    consecutiveOverlap = 'allow',
+    forbidRepeatShape = false,
+    moveCost = 0,
+    clearReward = 0,
+    maxTotalCleared = Infinity,
+    chainBonus = 0,
+    refillQueue,
+  } = options
+  const { moveIndex, previous } = state
+  const parts = [getGridKey(state.grid)]
+
+  if (Array.isArray(requireValue)) parts.push(`value:${Math.min(moveIndex, requireValue.length)}`)
+  if (shapeSchedule && shapeSchedule.length > 0) parts.push(`shape:${moveIndex % shapeSchedule.length}`)
+  if (consecutiveOverlap === 'forbid' && previous) parts.push(`box:${JSON.stringify(getBoundingBox(previous))}`)
+  if (forbidRepeatShape && previous) parts.push(`previous:${getSelectionType(previous)}`)
+  if (moveCost !== 0 || clearReward !== 0) parts.push(`budget:${state.budget}`)
+  if (Number.isFinite(maxTotalCleared)) parts.push(`cleared:${state.cleared}`)
+  if (chainBonus !== 0) parts.push(`chain:${state.chain}`)
+  if (refillQueue && refillQueue.length > 0) parts.push(`refill:${state.refillUsed}`)
+
+  return parts.join('/')
+}
+
+// Moves the game can go on with: the legal moves worth at least minMoveScore. Without any, the game is over,
+// just as the solver passes when no move is worth enough.
+const getPlayableMoves = (state: GameState, options: SolverOptions): Combination[] => {
+  return getLegalMoves(state, options).filter(cells => meetsMinMoveScore(cells, state, options))
+}
+
+interface OptimalCount {
+  bestScore: number
+  optimalSolutionCount: number
+  // Blocks left on the board by the best-scoring sequence that leaves the fewest
+  fewestRemaining: number
+}
+
+// The best score from a state along with the moves of its cleanest best-scoring sequence
+interface BestPaths extends OptimalCount {
+  path: Combination[]
+}
+
+// Turn moves played in order from a game state into solution steps
+const toSteps = (start: GameState, path: Combination[], options: SolverOptions): Step[] => {
+  let state = start
+  return path.map(cells => {
+    const score = calculateChainedMoveScore(cells, state.chain, options)
+    state = playMove(state, cells, options)
+    return { cells, sum: getCombinationSum(cells), score, gridAfter: state.grid }
+  })
+}
+
+// Count the move sequences reaching the best score when only moves passing allowMove may be played,
+// keeping the one that leaves the fewest blocks behind among them. A game is only over once it has no
+// playable move at all, so a state whose moves are all filtered out is a dead end rather than a finished game.
+// With a requiredClear, only sequences with a move clearing at least that many blocks count.
+// Gives up and returns null after visiting maxStates states.
+const countBestPaths = (
+  start: GameState,
+  options: SolverOptions,
+  maxStates: number,
+  allowMove: (cells: Combination) => boolean,
+  requiredClear = 0
+): BestPaths | null => {
+  const memo = new Map<string, BestPaths>()
+
+  const search = (state: GameState, reached: boolean): BestPaths | null => {
+    const key = requiredClear > 0 ? `${getStateKey(state, options)}/reached:${reached}` : getStateKey(state, options)
+    const cached = memo.get(key)
+    if (cached) return cached
+    if (memo.size >= maxStates) return null
+
+    const combinations = getPlayableMoves(state, options)
+
+    // The game only ends once no move is left, so a board with moves has no zero-move path
+    let result: BestPaths = combinations.length === 0 && reached
+      ? { bestScore: 0, optimalSolutionCount: 1, fewestRemaining: state.remaining, path: [] }
+      : { bestScore: -Infinity, optimalSolutionCount: 0, fewestRemaining: Infinity, path: [] }
+
+    for (const cells of combinations.filter(allowMove)) {
+      const child = search(playMove(state, cells, options), reached || cells.length >= requiredClear)
+      if (!child) return null
+
+      const score = calculateChainedMoveScore(cells, state.chain, options) + child.bestScore
+      if (score > result.bestScore) {
+        result = { ...child, bestScore: score, path: [cells, ...child.path] }
+      } else if (score === result.bestScore) {
+        const cleaner = child.fewestRemaining < result.fewestRemaining
+        result = {
+          bestScore: score,
+          optimalSolutionCount: result.optimalSolutionCount + child.optimalSolutionCount,
+          fewestRemaining: cleaner ? child.fewestRemaining : result.fewestRemaining,
+          path: cleaner ? [cells, ...child.path] : result.path,
+        }
+      }
+    }
+
+    memo.set(key, result)
+    return result
+  }
+
+  return search(start, requiredClear <= 0)
+}
+
+// Most blocks a single move can clear anywhere in the game, or null after visiting maxStates states
+const findLargestReachableClear = (start: GameState, options: SolverOptions, maxStates: number): number | null => {
+  const visited = new Set<string>()
+  let largest = 0
+
+  const visit = (state: GameState): boolean => {
+    const key = getStateKey(state, options)
+    if (visited.has(key)) return true
+    if (visited.size >= maxStates) return false
+
+    visited.add(key)
+    return getPlayableMoves(state, options).every(cells => {
+      largest = Math.max(largest, cells.length)
+      return visit(playMove(state, cells, options))
+    })
+  }
+
+  return visit(start) ? largest : null
+}
+
+// Mirror transforms of the board that map it and its rules onto themselves
+const getSymmetryTransforms = (grid: number[][], options: SolverOptions): ((cell: Cell) => string)[] => {
+  const { leftRight, topBottom } = getRuleSymmetry(grid, options)
+  const height = grid.length
+  const width = grid[0]?.length ?? 0
+
+  const transforms: [boolean, boolean][] = [[false, true], [true, false], [true, true]]
+  return transforms
+    .filter(([rows, cols]) => (!rows || topBottom) && (!cols || leftRight))
+    .map(([rows, cols]) => (cell: Cell) => {
+      return `${rows ? height - 1 - cell.row : cell.row}-${cols ? width - 1 - cell.col : cell.col}`
+    })
+}
+
+// Exhaustively search every move order to find the best achievable score, how many distinct
+// move sequences reach it and the fewest blocks any of them leaves. Only practical on small boards,
+// so the search gives up and returns null after visiting maxStates distinct boards.
+// With collapseSymmetricSolutions, sequences that mirror each other on a symmetric board count once
+// (orbits counted with Burnside's lemma: raw count plus the sequences each mirror leaves unchanged,
+// divided by the number of symmetries).
+export const countOptimalSolutions = (
+  grid: number[][],
+  options: SolverOptions = {},
+  maxStates = DEFAULT_MAX_STATES
+): OptimalCount | null => {
+  const internalOptions = toZeroIndexedOptions(options)
+  const start = startGame(grid, internalOptions)
+  const paths = countBestPaths(start, internalOptions, maxStates, () => true)
+  if (!paths) return null
+
+  const { bestScore, optimalSolutionCount, fewestRemaining } = paths
+  const result = { bestScore, optimalSolutionCount, fewestRemaining }
+  if (!options.collapseSymmetricSolutions) return result
+
+  const transforms = getSymmetryTransforms(start.grid, internalOptions)
+  let total = result.optimalSolutionCount
+  for (const transform of transforms) {
+    // A sequence is unchanged by a mirror only if every move is its own mirror image
+    const isSelfMirrored = (cells: Combination) => {
+      const positions = new Set(cells.map(c => `${c.row}-${c.col}`))
+      return cells.every(c => positions.has(transform(c)))
+    }
+    const fixed = countBestPaths(start, internalOptions, maxStates, isSelfMirrored)
+    if (!fixed) return null
+    if (fixed.bestScore === result.bestScore) total += fixed.optimalSolutionCount
+  }
+
+  return { ...result, optimalSolutionCount: total / (transforms.length + 1) }
+}
+
+// Play the best-scoring move sequence, breaking ties between equally scoring ones by the fewest blocks
+// left at the end. With objective 'maxSingleClear', only sequences reaching the biggest single clear
+// the board allows are considered. Returns null when the board is too big to search within maxStates states.
+export const solveExhaustive = (
+  grid: number[][],
+  options: SolverOptions = {},
+  maxStates = DEFAULT_MAX_STATES
+): Step[] | null => {
+  const internalOptions = toZeroIndexedOptions(options)
+  const start = startGame(grid, internalOptions)
+  const requiredClear = options.objective === 'maxSingleClear'
+    ? findLargestReachableClear(start, internalOptions, maxStates)
+    : 0
+  if (requiredClear === null) return null
+
+  const result = countBestPaths(start, internalOptions, maxStates, () => true, requiredClear)
+  if (!result) return null
+
+  const steps = toSteps(start, result.path, internalOptions)
+  const ordered = options.reverse ? reverseSteps(start.grid, steps) : steps
+  return options.oneIndexed ? shiftSteps(ordered, 1) : ordered
+}
+
+// Run the greedy solver and the exhaustive search and return the better solution: the higher score, then the
+// fewest blocks left (under objective 'maxSingleClear', the bigger single clear comes first). When the
+// exhaustive search gives up after maxStates states, the greedy solution is returned on its own and the
+// exhaustive search is listed in partialMethods.
+export const solveBestOf = (
+  grid: number[][],
+  options: SolverOptions = {},
+  maxStates = DEFAULT_MAX_STATES
+): BestOfSolution => {
+  // Compare forward solutions, whose last step holds the final board, and only reverse the winner
+  const forward = { ...options, reverse: false }
+  const start = startGame(grid, toZeroIndexedOptions(options)).grid
+  const finish = (steps: Step[], method: SolveMethod, partialMethods: SolveMethod[]): BestOfSolution => {
+    return { steps: options.reverse ? reverseSteps(start, steps) : steps, method, partialMethods }
+  }
+
+  const greedy = solvePuzzle(grid, forward)
+  const exhaustive = solveExhaustive(grid, forward, maxStates)
+  if (!exhaustive) return finish(greedy, 'greedy', ['exhaustive'])
+
+  const rank = (steps: Step[]) => ({
+    largestClear: options.objective === 'maxSingleClear' ? Math.max(0, ...steps.map(step => step.cells.length)) : 0,
+    score: steps.reduce((acc, step) => acc + step.score, 0),
+    remaining: countRemainingBlocks(steps[steps.length - 1]?.gridAfter ?? start),
+  })
+  const a = rank(greedy)
+  const b = rank(exhaustive)
+  const better = b.largestClear !== a.largestClear
+    ? b.largestClear > a.largestClear
+    : b.score > a.score || (b.score === a.score && b.remaining < a.remaining)
+  return better ? finish(exhaustive, 'exhaustive', []) : finish(greedy, 'greedy', [])
+}
+
+// Run every solver on the same board for comparison, keyed by method. The exhaustive search maps to null
+// when it gives up after maxStates states.
+export const solveAll = (
+  grid: number[][],
+  options: SolverOptions = {},
+  maxStates = DEFAULT_MAX_STATES
+): SolveAllResult => {
+  const withScore = (steps: Step[] | null): MethodSolution | null => {
+    return steps && { steps, score: steps.reduce((acc, step) => acc + step.score, 0) }
+  }
+
+  return {
+    greedy: withScore(solvePuzzle(grid, options)),
+    exhaustive: withScore(solveExhaustive(grid, options, maxStates)),
+  }
+}
+
+// Whether the greedy solver reaches the best achievable score, i.e. whether its heuristic costs nothing on this
+// board. Returns null when the board is too big to search exhaustively within maxStates states.
+export const solversAgree = (
+  grid: number[][],
+  options: SolverOptions = {},
+  maxStates = DEFAULT_MAX_STATES
+): boolean | null => {
+  const optimal = countOptimalSolutions(grid, options, maxStates)
+  return optimal && solveScoreOnly(grid, options) === optimal.bestScore
+}
+
+// Fewest moves that clear every block, found breadth-first so the first empty board reached is the
+// shallowest. Returns null if no full clear exists or none was found within maxStates boards.
+export const findMinMovesFullClear = (
+  grid: number[][],
+  options: SolverOptions = {},
+  maxStates = DEFAULT_MAX_STATES
+): number | null => {
+  const internalOptions = toZeroIndexedOptions(options)
+  const start = startGame(grid, internalOptions)
+  const visited = new Set<string>([getStateKey(start, internalOptions)])
+  let frontier = [start]
+
+  for (let moves = 0; frontier.length > 0; moves++) {
+    const next: GameState[] = []
+    for (const state of frontier) {
+      if (state.remaining === 0) return moves
+
+      for (const cells of getPlayableMoves(state, internalOptions)) {
+        const child = playMove(state, cells, internalOptions)
+        const key = getStateKey(child, internalOptions)
+        if (visited.has(key)) continue
+        if (visited.size >= maxStates) return null
+
+        visited.add(key)
+        next.push(child)
+      }
+    }
+    frontier = next
+  }
+
+  return null
+}
+
+// One non-dominated point while searching, with the moves of a path that reaches it and the blocks it leaves
+interface FrontierPoint {
+  score: number
+  moves: number
+  remaining: number
+  path: Combination[]
+}
+
+// Keep only points that no other point beats on both score and move count, one path per point,
+// picking the path that leaves the fewest blocks
+const pruneDominated = (points: FrontierPoint[]): FrontierPoint[] => {
+  const sorted = [...points].sort((a, b) => b.score - a.score || b.moves - a.moves || a.remaining - b.remaining)
+  const frontier: FrontierPoint[] = []
+  for (const point of sorted) {
+    if (frontier.every(kept => point.moves > kept.moves)) frontier.push(point)
+  }
+  return frontier
+}
+
+// Exhaustively find the trade-off between the two objectives the solver supports: the best total score
+// and the most moves played. Each returned solution is one that no other beats on both, sorted from the
+// highest score down. Returns null once the search visits more than maxStates boards.
+export const findParetoFrontier = (
+  grid: number[][],
+  options: SolverOptions = {},
+  maxStates = DEFAULT_MAX_STATES
+): ParetoSolution[] | null => {
+  const internalOptions = toZeroIndexedOptions(options)
+  const start = startGame(grid, internalOptions)
+  const memo = new Map<string, FrontierPoint[]>()
+
+  const search = (state: GameState): FrontierPoint[] | null => {
+    const key = getStateKey(state, internalOptions)
+    const cached = memo.get(key)
+    if (cached) return cached
+    if (memo.size >= maxStates) return null
+
+    const combinations = getPlayableMoves(state, internalOptions)
+    const points: FrontierPoint[] = combinations.length === 0
+      ? [{ score: 0, moves: 0, remaining: state.remaining, path: [] }]
+      : []
+
+    for (const cells of combinations) {
+      const child = search(playMove(state, cells, internalOptions))
+      if (!child) return null
+
+      const moveScore = calculateChainedMoveScore(cells, state.chain, internalOptions)
+      for (const point of child) {
+        points.push({ ...point, score: point.score + moveScore, moves: point.moves + 1, path: [cells, ...point.path] })
+      }
+    }
+
+    const frontier = pruneDominated(points)
+    memo.set(key, frontier)
+    return frontier
+  }
+
+  const frontier = search(start)
+  if (!frontier) return null
+
+  return frontier.map(({ score, moves, path }) => {
+    const steps = toSteps(start, path, internalOptions)
+    return { score, moves, steps: options.oneIndexed ? shiftSteps(steps, 1) : steps }
+  })
+}
+
+// Find blocks that no order of play ever clears, by collecting every cell cleared by some move
+// across all reachable boards. The search stops after visiting maxStates boards, so on large boards
+// the result is approximate and may list blocks that a deeper line would still clear.
+// With gravity the positions refer to where a block is cleared, not which block it started as.
+export const findPermanentlyStranded = (
+  grid: number[][],
+  options: SolverOptions = {},
+  maxStates = DEFAULT_MAX_STATES
+): [number, number][] => {
+  const internalOptions = toZeroIndexedOptions(options)
+  const start = startGame(grid, internalOptions)
+  const blocks = start.grid
+    .flatMap((row, r) => row.map((value, c): [number, number, number] => [r, c, value]))
+    .filter(([, , value]) => value > 0)
+  // Starting positions no move has cleared yet. Under gravity moves also clear cells that started empty,
+  // so only an empty set means there is nothing left to find.
+  const uncleared = new Set(blocks.map(([row, col]) => `${row}-${col}`))
+  const visited = new Set<string>()
+  const pending = [start]
+
+  while (visited.size < maxStates && uncleared.size > 0) {
+    const state = pending.pop()
+    if (!state) break
+
+    const key = getStateKey(state, internalOptions)
+    if (visited.has(key)) continue
+    visited.add(key)
+
+    for (const cells of getPlayableMoves(state, internalOptions)) {
+      cells.forEach(c => uncleared.delete(`${c.row}-${c.col}`))
+      pending.push(playMove(state, cells, internalOptions))
+    }
+  }
+
+  const offset = options.oneIndexed ? 1 : 0
+  return blocks
+    .filter(([row, col]) => uncleared.has(`${row}-${col}`))
+    .map(([row, col]): [number, number] => [row + offset, col + offset])
+}
+
+// How close a solution gets to the best possible score, from 0 to 1. The reference is the exhaustive optimum
+// when the board is small enough to search, otherwise the better of the greedy solver's score and the
+// solution's own. Returns null if the solution plays an illegal move.
+export const getSolutionEfficiency = (
+  grid: number[][],
+  steps: Step[],
+  options: SolverOptions = {},
+  maxStates = DEFAULT_MAX_STATES
+): number | null => {
+  const result = verifySolution(grid, steps, options)
+  if (!result.valid) return null
+
+  const optimal = countOptimalSolutions(grid, options, maxStates)
+  const reference = optimal?.bestScore ?? Math.max(solveScoreOnly(grid, options), result.score)
+  return reference > 0 ? result.score / reference : 1
+}
@@ -0,0 +1,28 @@
+import { describe, expect, it } from 'vitest'
+import { shufflePreservingMoves } from './generator'
+import { findValidCombinations } from './solver'
+import { randomBoard } from './testHelpers'
+
+describe('shufflePreservingMoves', () => {
+  const seeds = [1, 2, 3, 4, 5, 6, 7, 8]
+
+  it.each(seeds)('keeps the number of legal first moves with seed %s', seed => {
+    for (const grid of [randomBoard(4, 4, seed), randomBoard(3, 5, seed)]) {
+      const shuffled = shufflePreservingMoves(grid, seed)
+      expect(findValidCombinations(shuffled)).toHaveLength(findValidCombinations(grid).length)
+    }
+  })
+
+  it.each(seeds)('keeps the number of legal first moves under a lopsided playableMask with seed %s', seed => {
+    const grid = randomBoard(4, 4, seed)
+    const options = { playableMask: grid.map((row, r) => row.map((_, c) => r + c < 5)) }
+    const shuffled = shufflePreservingMoves(grid, seed, options)
+    expect(findValidCombinations(shuffled, options)).toHaveLength(findValidCombinations(grid, options).length)
+  })
+
+  it('gives the same board for the same seed and a different one for some seed', () => {
+    const grid = randomBoard(4, 5, 3)
+    expect(shufflePreservingMoves(grid, 11)).toEqual(shufflePreservingMoves(grid, 11))
+    expect(seeds.some(seed => JSON.stringify(shufflePreservingMoves(grid, seed)) !== JSON.stringify(grid))).toBe(true)
+  })
+})
@@ -0,0 +1,85 @@
+import type { SolverOptions } from '../types'
+import { countOptimalSolutions } from './exhaustive'
+import { findValidCombinations, solveScoreOnly } from './solver'
+import { createEmptyGrid, createRng } from './utils'
+
+const DEFAULT_MAX_ATTEMPTS = 500
+// Random boards tried at each block count before allowing one more block
+const ATTEMPTS_PER_BLOCK_COUNT = 20
+
+// Produce a visually different board with exactly the same moves, picked deterministically from the seed.
+// Only mirror flips (and transposing square boards) are used: they keep every row and column a contiguous
+// line, so each horizontal, vertical and rectangular combination maps onto a legal one of the same size.
+// Arbitrary row or column permutations would break up vertical or horizontal runs. Rules tied to fixed
+// positions can still tell the boards apart, so a shuffle that changes the number of legal first moves
+// is rejected and the next one is tried, down to the untouched board.
+export const shufflePreservingMoves = (
+  grid: number[][],
+  seed: number,
+  options: SolverOptions = {}
+): number[][] => {
+  const random = createRng(seed)
+  const height = grid.length
+  const width = grid[0]?.length ?? 0
+
+  const flipRows = random() < 0.5
+  const flipCols = random() < 0.5
+  const transpose = height === width && random() < 0.5
+
+  const moveCount = findValidCombinations(grid, options).length
+  const candidates = [
+    { flipRows, flipCols, transpose },
+    { flipRows, flipCols, transpose: false },
+    { flipRows, flipCols: false, transpose: false },
+    { flipRows: false, flipCols, transpose: false },
+  ]
+
+  for (const candidate of candidates) {
+    const flipped = grid.map((_, r) => {
+      const source = grid[candidate.flipRows ? height - 1 - r : r]
+      return source.map((_, c) => source[candidate.flipCols ? width - 1 - c : c])
+    })
+    const shuffled = candidate.transpose ? flipped.map((row, r) => row.map((_, c) => flipped[c][r])) : flipped
+    if (findValidCombinations(shuffled, options).length === moveCount) return shuffled
+  }
+
+  return grid.map(row => [...row])
+}
+
+// Search for the sparsest board of the given size whose best possible score reaches targetScore.
+// Boards are sampled from the seed starting with as few blocks as could reach the target, adding a block
+// after every few misses. The greedy score is a cheap lower bound, so the exhaustive search only runs when
+// greedy falls short. Returns null if no board is found within maxAttempts.
+export const generateBoardForScore = (
+  height: number,
+  width: number,
+  targetScore: number,
+  seed: number,
+  options: SolverOptions = {},
+  maxAttempts = DEFAULT_MAX_ATTEMPTS
+): number[][] | null => {
+  const random = createRng(seed)
+  const totalCells = height * width
+
+  for (let attempt = 0; attempt < maxAttempts; attempt++) {
+    const blockCount = Math.max(1, targetScore) + Math.floor(attempt / ATTEMPTS_PER_BLOCK_COUNT)
+    if (blockCount > totalCells) return null
+
+    // Partial Fisher-Yates shuffle picks the cells that get a block
+    const cells = Array.from({ length: totalCells }, (_, idx) => idx)
+    const grid = createEmptyGrid(height, width)
+    for (let i = 0; i < blockCount; i++) {
+      const j = i + Math.floor(random() * (totalCells - i))
+      const picked = cells[j]
+      cells[j] = cells[i]
+      cells[i] = picked
+      grid[Math.floor(picked / width)][picked % width] = 1 + Math.floor(random() * 9)
+    }
+
+    if (solveScoreOnly(grid, options) >= targetScore) return grid
+    const optimal = countOptimalSolutions(grid, options)
+    if (optimal && optimal.bestScore >= targetScore) return grid
+  }
+
+  return null
+}
@@ -0,0 +1,603 @@
+import { describe, expect, it } from 'vitest'
+import type { ComboShape, Combination, SolverOptions } from '../types'
+import {
+  applyMove,
+  findLargestRectangleClear,
+  findMovesBounded,
+  findNearMissMoves,
+  findValidCombinations,
+  getAffectedLines,
+  getBestLinePerRow,
+  getMoveQuality,
+  getMovesAfter,
+  isDeadBoard,
+  isTargetFeasible,
+  playMove,
+  solvePuzzle,
+  solveScoreOnly,
+  solvePuzzleWithTrace,
+  solvePuzzleWithTree,
+  startGame,
+  toZeroIndexedOptions,
+} from './solver'
+import { getRuleCases, randomBoard, totalScore } from './testHelpers'
+import { applyGravity, countRemainingBlocks, createRng, getSelectionType, reverseSteps } from './utils'
+import { verifySolution } from './verify'
+
+describe('rectangleMode', () => {
+  const solid = [[3, 2], [1, 4]]
+  // The two 5s only form a rectangle together with the empty cells between them
+  const sparse = [[5, 0], [0, 5]]
+
+  it('finds a fully filled rectangle in both modes', () => {
+    for (const rectangleMode of ['solid', 'sparse'] as const) {
+      const rectangles = findValidCombinations(solid, { rectangleMode, shapeSchedule: ['Rectangle'] })
+      expect(rectangles.map(cells => cells.length)).toEqual([4])
+    }
+  })
+
+  it('only finds a rectangle spanning empty cells in sparse mode', () => {
+    expect(findValidCombinations(sparse, { rectangleMode: 'solid' })).toEqual([])
+    expect(findValidCombinations(sparse, { rectangleMode: 'sparse' }).map(cells => cells.length)).toEqual([2])
+  })
+})
+
+describe('minMoveScore', () => {
+  it('plays a move that meets the threshold even when the objective ranks a smaller one first', () => {
+    const steps = solvePuzzle([[1, 9, 2, 3, 5]], { objective: 'maxMoves', minMoveScore: 3 })
+
+    expect(steps.map(step => step.cells.map(c => c.col))).toEqual([[2, 3, 4], []])
+    expect(steps[1].score).toBe(0)
+  })
+
+  it('passes when every available move is below the threshold', () => {
+    const steps = solvePuzzle([[1, 9, 0, 4, 6]], { minMoveScore: 3 })
+    expect(steps).toHaveLength(1)
+    expect(steps[0].cells).toEqual([])
+  })
+})
+
+describe('perGenerationBudgetMs', () => {
+  // The only move is a rectangle in the bottom-right corner, found last in the scan
+  const grid = [
+    [1, 0, 0, 0, 0],
+    [0, 1, 0, 0, 0],
+    [0, 0, 0, 2, 3],
+    [0, 0, 0, 1, 4],
+  ]
+
+  it('keeps scanning past an exhausted time slice until a move is found', () => {
+    expect(findValidCombinations(grid, { perGenerationBudgetMs: 0 })).toHaveLength(1)
+    expect(solvePuzzle(grid, { perGenerationBudgetMs: 0 })).toHaveLength(1)
+  })
+
+  it('does not report a board as dead because the slice ran out', () => {
+    expect(isDeadBoard(grid, { perGenerationBudgetMs: 0 })).toBe(false)
+    expect(isDeadBoard([[1, 2, 3]], { perGenerationBudgetMs: 0 })).toBe(true)
+  })
+})
+
+describe('isDeadBoard', () => {
+  it('checks the board left after the hypothetical edits', () => {
+    expect(isDeadBoard([[1, 9, 5]])).toBe(false)
+    expect(isDeadBoard([[1, 9, 5]], { preCleared: [[0, 0]] })).toBe(true)
+    expect(isDeadBoard([[1, 9, 5]], { oneIndexed: true, preCleared: [[1, 1]] })).toBe(true)
+  })
+
+  it('only counts moves the per-move rules allow', () => {
+    expect(isDeadBoard([[1, 9, 5, 5]], { requireValue: [3] })).toBe(true)
+  })
+})
+
+describe('line enumeration', () => {
+  // Reference check: every subset of the blocks in a row summing to 10 whose ends skip over at most maxGaps
+  // unselected blocks
+  const bruteForceRow = (values: number[], maxGaps: number): string[] => {
+    const blocks = values.map((value, col) => ({ value, col })).filter(cell => cell.value > 0)
+    const found: string[] = []
+    for (let mask = 1; mask < 1 << blocks.length; mask++) {
+      const picked = blocks.filter((_, idx) => mask & (1 << idx))
+      if (picked.reduce((acc, cell) => acc + cell.value, 0) !== 10) continue
+
+      const first = picked[0].col
+      const last = picked[picked.length - 1].col
+      const skipped = blocks.filter(cell => cell.col > first && cell.col < last && !picked.includes(cell))
+      if (skipped.length <= maxGaps) found.push(picked.map(cell => cell.col).join(','))
+    }
+    return found.sort()
+  }
+
+  it('finds exactly the valid runs along a line', () => {
+    const random = createRng(42)
+    for (let trial = 0; trial < 200; trial++) {
+      const values = Array.from({ length: 3 + Math.floor(random() * 9) }, () => {
+        return random() < 0.2 ? 0 : 1 + Math.floor(random() * 9)
+      })
+      const maxGaps = trial % 3
+
+      const found = findValidCombinations([values], { maxGaps }).map(cells => cells.map(c => c.col).join(','))
+      expect(found.sort()).toEqual(bruteForceRow(values, maxGaps))
+    }
+  })
+
+  it('handles very wide and tall lines without enumerating every subset', () => {
+    const wide = [Array(60).fill(1)]
+    const tall = wide[0].map(value => [value])
+
+    expect(findValidCombinations(wide, { maxGaps: 2 }).length).toBeGreaterThan(0)
+    for (const grid of [wide, tall]) {
+      const steps = solvePuzzle(grid)
+      expect(steps).toHaveLength(6)
+      expect(verifySolution(grid, steps).valid).toBe(true)
+    }
+  })
+})
+
+describe('adversarial grids', () => {
+  it('rejects malformed grids with an error', () => {
+    expect(() => solvePuzzle([[1, 2], [3]])).toThrow('same length')
+    for (const value of [10, -1, 1.5, NaN]) {
+      expect(() => solvePuzzle([[1, value]])).toThrow('0 to 9')
+    }
+  })
+
+  it('solves empty boards to an empty solution', () => {
+    expect(solvePuzzle([])).toEqual([])
+    expect(solvePuzzle([[]])).toEqual([])
+  })
+
+  it('only produces solutions that verify, across random shapes and options', () => {
+    const random = createRng(7)
+    for (let trial = 0; trial < 40; trial++) {
+      const height = 1 + Math.floor(random() * 12)
+      const width = 1 + Math.floor(random() * 12)
+      const grid = Array.from({ length: height }, () => {
+        return Array.from({ length: width }, () => (random() < 0.2 ? 0 : 1 + Math.floor(random() * 9)))
+      })
+      const options: SolverOptions = {
+        maxGaps: trial % 3,
+        wrapHorizontal: trial % 4 === 1,
+        wrapVertical: trial % 5 === 2,
+        gravity: (['none', 'down', 'left'] as const)[trial % 3],
+      }
+
+      const steps = solvePuzzle(grid, options)
+      expect(verifySolution(grid, steps, options).valid).toBe(true)
+    }
+  })
+})
+
+describe('gravity with a playable mask', () => {
+  // The wall at (2, 0) holds up the 7 above it, and the 4 below it can't fall into it
+  const grid = [
+    [7, 0],
+    [0, 3],
+    [0, 4],
+    [4, 6],
+  ]
+  const playableMask = [
+    [true, true],
+    [true, true],
+    [false, true],
+    [true, true],
+  ]
+
+  it('keeps blocks from falling into or through walls', () => {
+    const board = applyMove(grid, [{ row: 2, col: 1, value: 4 }, { row: 3, col: 1, value: 6 }], {
+      gravity: 'down',
+      playableMask,
+    })
+    expect(board).toEqual([
+      [0, 0],
+      [7, 0],
+      [0, 0],
+      [4, 3],
+    ])
+  })
+
+  it('compacts each lane between walls towards the left', () => {
+    expect(applyGravity([[0, 5, 0, 2, 0, 1]], 'left', (_, col) => col === 2)).toEqual([[5, 0, 0, 2, 1, 0]])
+  })
+})
+
+describe('gravity with frozen rows', () => {
+  it('leaves frozen blocks in place so they are never cleared', () => {
+    // Clearing the 1 and 9 would let the frozen 5 drop next to the other 5 and become clearable
+    const grid = [
+      [5, 0],
+      [1, 0],
+      [9, 5],
+    ]
+    const steps = solvePuzzle(grid, { gravity: 'down', frozenRows: 1 })
+
+    expect(steps).toHaveLength(1)
+    expect(steps[0].gridAfter).toEqual([
+      [5, 0],
+      [0, 0],
+      [0, 5],
+    ])
+  })
+})
+
+describe('autoCrop', () => {
+  // Blocks only in the middle of a wider board, so the crop drops empty margins on every side
+  const grid = [
+    [0, 0, 0, 0, 0, 0, 0],
+    [0, 1, 9, 3, 7, 5, 0],
+    [0, 9, 1, 7, 3, 5, 0],
+    [0, 2, 8, 4, 6, 1, 0],
+    [0, 0, 0, 0, 0, 0, 0],
+  ]
+  const cases: [string, SolverOptions][] = [
+    ['defaults', {}],
+    ['centralityBonus', { centralityBonus: 3 }],
+    ['edgeFirstWeight', { edgeFirstWeight: 2 }],
+    ['style', { style: 'efficient' }],
+    ['scoreMove', { scoreMove: (cells, board) => cells[0].row / board.length }],
+  ]
+
+  it.each(cases)('picks the same moves as an uncropped solve with %s', (_, options) => {
+    expect(solvePuzzle(grid, { ...options, autoCrop: true })).toEqual(solvePuzzle(grid, options))
+  })
+})
+
+describe('style presets', () => {
+  const grid = [
+    [6, 1, 5, 9, 9, 3],
+    [6, 7, 4, 9, 5, 5],
+    [2, 4, 3, 2, 5, 1],
+    [4, 7, 3, 2, 1, 4],
+    [6, 8, 3, 2, 6, 5],
+    [7, 2, 7, 5, 7, 9],
+  ]
+
+  it('plays the default weights for balanced', () => {
+    expect(solvePuzzle(grid, { style: 'balanced' })).toEqual(solvePuzzle(grid))
+  })
+
+  it('plays a different solution for each style', () => {
+    const solutions = (['aggressive', 'efficient', 'balanced'] as const).map(style =>
+      JSON.stringify(solvePuzzle(grid, { style }).map(step => step.cells))
+    )
+
+    expect(new Set(solutions).size).toBe(3)
+  })
+
+  it('never gives up blocks for a rectangle when aggressive', () => {
+    // The top row clears 5 blocks, the rectangle to its left only 4
+    const board = [[0, 0, 2, 2, 2, 2, 2], [2, 3, 0, 0, 0, 0, 0], [3, 2, 0, 0, 0, 0, 0]]
+    expect(solvePuzzle(board, { style: 'aggressive' })[0].cells).toHaveLength(5)
+  })
+})
+
+describe('getMoveQuality', () => {
+  // Clearing the top row scores 3, while pairing the 1 with the 9 below takes the 1 the row needs
+  const grid = [[1, 1, 8], [9, 0, 0]]
+
+  it('gives the best move no regret', () => {
+    expect(getMoveQuality(grid, [[0, 0], [0, 1], [0, 2]])).toEqual({ moveScore: 3, bestScore: 3, regret: 0 })
+  })
+
+  it('gives a move that blocks a bigger clear positive regret', () => {
+    expect(getMoveQuality(grid, [[0, 0], [1, 0]])).toEqual({ moveScore: 2, bestScore: 3, regret: 1 })
+  })
+
+  it('continues the game under the rules the move was played by', () => {
+    // The second row is another horizontal move, which forbidRepeatShape doesn't allow right after the first
+    expect(getMoveQuality([[1, 9], [2, 8]], [[0, 0], [0, 1]], { forbidRepeatShape: true }))
+      .toMatchObject({ moveScore: 2 })
+  })
+})
+
+describe('getMovesAfter', () => {
+  it('lists the moves left after the given one', () => {
+    const moves = getMovesAfter([[1, 9], [2, 8]], [[0, 0], [0, 1]])
+    expect(moves?.map(cells => cells.map(c => [c.row, c.col]))).toEqual([[[1, 0], [1, 1]]])
+  })
+
+  it('applies the rules that depend on the move just played', () => {
+    expect(getMovesAfter([[1, 9], [2, 8]], [[0, 0], [0, 1]], { forbidRepeatShape: true })).toEqual([])
+  })
+
+  it('returns null for a move that is not legal', () => {
+    expect(getMovesAfter([[1, 9], [2, 8]], [[0, 0], [1, 0]])).toBeNull()
+  })
+})
+
+describe('oneIndexed entry points', () => {
+  const grid = [[1, 9, 0, 4, 6], [2, 8, 5, 5, 0], [3, 7, 1, 2, 7]]
+  const shift = <T>(value: T): T => JSON.parse(JSON.stringify(value), (key, field) => {
+    return (key === 'row' || key === 'col' || key.endsWith('Row') || key.endsWith('Col')) ? field + 1 : field
+  })
+  const cases: [string, (options: SolverOptions) => unknown][] = [
+    ['findValidCombinations', options => findValidCombinations(grid, options)],
+    ['findMovesBounded', options => findMovesBounded(grid, 3, options)],
+    ['findLargestRectangleClear', options => findLargestRectangleClear(grid, options)],
+    ['getBestLinePerRow', options => getBestLinePerRow(grid, options)],
+    ['findNearMissMoves', options => findNearMissMoves(grid, 10, 1, options)],
+    ['solvePuzzleWithTree', options => solvePuzzleWithTree(grid, 20, options)],
+    ['solvePuzzleWithTrace steps', options => solvePuzzleWithTrace(grid, 3, options).steps],
+  ]
+
+  it.each(cases)('%s reports the same cells one row and column further', (_, run) => {
+    expect(run({ oneIndexed: true, colDividers: [3] })).toEqual(shift(run({ colDividers: [2] })))
+  })
+
+  it('lists affected lines in one-based coordinates', () => {
+    const edited = grid.map(row => [...row])
+    edited[2][4] = 0
+    const { rows, cols, rectangles } = getAffectedLines(grid, edited)
+    expect(getAffectedLines(grid, edited, { oneIndexed: true })).toEqual({
+      rows: rows.map(row => row + 1),
+      cols: cols.map(col => col + 1),
+      rectangles: shift(rectangles),
+    })
+    expect(rows).toEqual([2])
+  })
+
+  it('writes traces in one-based coordinates', () => {
+    expect(solvePuzzleWithTrace([[1, 9]], 1, { oneIndexed: true }).trace).toContain('(1,1) (1,2)')
+  })
+})
+
+describe('remaining block count', () => {
+  const grid = randomBoard(6, 6, 3)
+  const hidden = grid.map((row, r) => row.map((value, c) => ((r + c) % 4 === 0 ? -1 : value)))
+  const cases: [string, number[][], SolverOptions][] = [
+    ...getRuleCases(grid).map(([name, options]): [string, number[][], SolverOptions] => [name, grid, options]),
+    ['hidden cells', hidden, { hiddenValues: grid }],
+    ['refills without gravity', grid, { refillQueue: [4, 6, 0, 5, 5, 3, 7] }],
+  ]
+
+  it.each(cases)('stays equal to a fresh count across a solve with %s', (_, board, options) => {
+    const internalOptions = toZeroIndexedOptions(options)
+    const steps = solvePuzzle(board, internalOptions)
+    let state = startGame(board, internalOptions)
+
+    for (const step of steps.filter(({ cells }) => cells.length > 0)) {
+      state = playMove(state, step.cells, internalOptions)
+      expect(state.remaining).toBe(countRemainingBlocks(state.grid))
+    }
+    expect(state.remaining).toBe(countRemainingBlocks(steps[steps.length - 1]?.gridAfter ?? state.grid))
+  })
+})
+
+describe('gravity', () => {
+  // Clearing the 1 and 9 drops the top 5 next to the bottom-right one
+  const grid = [[5, 0], [1, 3], [9, 5]]
+
+  it('plays a move that only exists once the blocks have fallen', () => {
+    const steps = solvePuzzle(grid, { gravity: 'down' })
+    expect(steps).toHaveLength(2)
+    expect(steps[1].cells.map(c => [c.row, c.col])).toEqual([[2, 0], [2, 1]])
+  })
+
+  it('has no such move without gravity', () => {
+    expect(solvePuzzle(grid)).toHaveLength(1)
+  })
+})
+
+describe('hidden cells', () => {
+  // The hidden cell between the 4 and the 6 turns out to be empty once the 2 and 8 below it clear
+  const grid = [[4, -1, 6], [2, 8, 0]]
+  const options: SolverOptions = { hiddenValues: [[4, 0, 6], [2, 8, 0]] }
+
+  it('blocks a run until an adjacent clear reveals it', () => {
+    const positions = (cells: Combination) => cells.map(c => [c.row, c.col])
+    expect(findValidCombinations(grid, options).map(positions)).toEqual([[[1, 0], [1, 1]]])
+    expect(solvePuzzle(grid, options).map(step => positions(step.cells))).toEqual([[[1, 0], [1, 1]], [[0, 0], [0, 2]]])
+  })
+})
+
+describe('wrapVertical', () => {
+  const column = [[4], [9], [6]]
+
+  it('joins a column run from the last row to the first', () => {
+    const rows = findValidCombinations(column, { wrapVertical: true }).map(cells => cells.map(c => c.row).sort())
+    expect(rows).toEqual([[0, 2]])
+  })
+
+  it('keeps the column edges apart without wrapping', () => {
+    expect(findValidCombinations(column)).toEqual([])
+    expect(findValidCombinations(column, { wrapHorizontal: true })).toEqual([])
+  })
+})
+
+describe('objective', () => {
+  // The bottom row clears three blocks at once, but takes both 3s each 7 above needs
+  const grid = [[7, 0, 7], [3, 4, 3]]
+
+  it('plays the biggest clear under the default objective', () => {
+    expect(solvePuzzle(grid).map(step => step.cells.length)).toEqual([3])
+  })
+
+  it('plays more moves under maxMoves on the same board', () => {
+    expect(solvePuzzle(grid, { objective: 'maxMoves' }).map(step => step.cells.length)).toEqual([2, 2])
+  })
+})
+
+describe('reverse', () => {
+  const grid = randomBoard(5, 5, 2)
+  const forward = solvePuzzle(grid)
+  const backward = solvePuzzle(grid, { reverse: true })
+
+  it('rebuilds the initial board when replayed from the final one', () => {
+    expect(backward.map(step => step.cells)).toEqual([...forward].reverse().map(step => step.cells))
+    expect(backward[backward.length - 1].gridAfter).toEqual(grid)
+  })
+
+  it('gives back the forward solution when reversed again', () => {
+    expect(reverseSteps(forward[forward.length - 1].gridAfter, backward)).toEqual(forward)
+  })
+})
+
+describe('solvePuzzleWithTree', () => {
+  const grid = randomBoard(5, 5, 4)
+
+  it('roots the tree at the input board', () => {
+    const { tree } = solvePuzzleWithTree(grid, 50)
+    expect(tree[0]).toMatchObject({ id: 0, parentId: null, cells: [], score: 0, grid })
+  })
+
+  it('never records more nodes than the cap', () => {
+    for (const maxTreeNodes of [0, 1, 5, 20]) {
+      expect(solvePuzzleWithTree(grid, maxTreeNodes).tree.length).toBeLessThanOrEqual(maxTreeNodes)
+    }
+    expect(solvePuzzleWithTree(grid, 5).tree).toHaveLength(5)
+  })
+
+  it('solves the same way as without a tree', () => {
+    expect(solvePuzzleWithTree(grid, 5).steps).toEqual(solvePuzzle(grid))
+  })
+})
+
+describe('requireValue', () => {
+  // Both rows clear, but only the bottom one holds a 3
+  const grid = [[1, 9, 0], [3, 7, 0]]
+
+  it('only allows moves containing the required value', () => {
+    const moves = findValidCombinations(grid, { requireValue: 3 })
+    expect(moves.map(cells => cells.map(c => c.value))).toEqual([[3, 7]])
+    expect(solvePuzzle(grid, { requireValue: 3 })[0].cells.map(c => c.value)).toContain(3)
+  })
+
+  it('requires a value per move when given a list', () => {
+    const steps = solvePuzzle(grid, { requireValue: [3, 9] })
+    expect(steps.map(step => step.cells.map(c => c.value))).toEqual([[3, 7], [1, 9]])
+  })
+})
+
+describe('shapeSchedule', () => {
+  it('alternates horizontal and vertical moves', () => {
+    const grid = randomBoard(6, 6, 1)
+    const schedule: ComboShape[] = ['Horizontal', 'Vertical']
+    const shapes = solvePuzzle(grid, { shapeSchedule: schedule }).map(step => getSelectionType(step.cells))
+
+    expect(shapes.length).toBeGreaterThan(2)
+    expect(shapes).toEqual(shapes.map((_, idx) => schedule[idx % schedule.length]))
+    expect(solvePuzzle(grid).map(step => getSelectionType(step.cells))).toContain('Rectangle')
+  })
+})
+
+describe('playableMask', () => {
+  // An L-shaped region: the left column and the bottom row
+  const grid = [[4, 6, 5], [3, 5, 5], [3, 2, 8]]
+  const playableMask = grid.map((row, r) => row.map((_, c) => c === 0 || r === 2))
+  const insideMask = (cells: Combination) => cells.every(c => playableMask[c.row][c.col])
+
+  it('only finds moves inside the L', () => {
+    const moves = findValidCombinations(grid, { playableMask })
+    expect(moves.map(cells => cells.map(c => c.value))).toEqual([[2, 8], [4, 3, 3]])
+    expect(findValidCombinations(grid).every(insideMask)).toBe(false)
+  })
+
+  it('never plays a cell outside the L', () => {
+    const steps = solvePuzzle(grid, { playableMask })
+    expect(steps.length).toBeGreaterThan(0)
+    expect(steps.every(step => insideMask(step.cells))).toBe(true)
+  })
+})
+
+describe('dividers', () => {
+  it('keeps horizontal runs from crossing a vertical divider', () => {
+    const cols = (grid: number[][], options: SolverOptions) => {
+      return findValidCombinations(grid, options).map(cells => cells.map(c => c.col))
+    }
+    expect(cols([[3, 7, 2, 8]], {})).toEqual([[0, 1], [2, 3]])
+    expect(cols([[3, 7, 2, 8]], { colDividers: [1] })).toEqual([[2, 3]])
+    expect(cols([[4, 6]], { colDividers: [1] })).toEqual([])
+  })
+
+  it('leaves runs along the divider alone', () => {
+    expect(findValidCombinations([[4], [6]], { colDividers: [1] })).toHaveLength(1)
+  })
+})
+
+describe('exploitSymmetry', () => {
+  it('scores the same as the full solve on a symmetric board', () => {
+    const grid = [[1, 9, 9, 1], [5, 2, 2, 5], [5, 8, 8, 5]]
+    const steps = solvePuzzle(grid, { exploitSymmetry: true })
+    expect(verifySolution(grid, steps)).toEqual({ valid: true, score: solveScoreOnly(grid) })
+  })
+
+  it('keeps a move whose mirror image the rules forbid', () => {
+    // Only the bottom row can clear once the top one is frozen, so it has no playable twin
+    expect(solvePuzzle([[1, 9], [0, 0], [1, 9]], { frozenRows: 1, exploitSymmetry: true })).toHaveLength(1)
+  })
+})
+
+describe('clearOrder', () => {
+  const grid = [[1, 9, 0], [2, 8, 0], [3, 7, 0]]
+
+  it.each([
+    ['bottomUp', [2, 1, 0]],
+    ['topDown', [0, 1, 2]],
+  ] as const)('never clears a row while blocks remain beyond it with %s', (clearOrder, rowOrder) => {
+    const steps = solvePuzzle(grid, { clearOrder })
+    expect(steps.map(step => step.cells[0].row)).toEqual(rowOrder)
+
+    steps.forEach((step, idx) => {
+      const before = idx === 0 ? grid : steps[idx - 1].gridAfter
+      const rows = step.cells.map(c => c.row)
+      const inMove = new Set(step.cells.map(c => `${c.row}-${c.col}`))
+      const isBeyond = (row: number) => clearOrder === 'bottomUp' ? row > Math.min(...rows) : row < Math.max(...rows)
+      const leftBeyond = before.flatMap((row, r) => {
+        return row.map((value, c) => value > 0 && isBeyond(r) && !inMove.has(`${r}-${c}`))
+      })
+      expect(leftBeyond).not.toContain(true)
+    })
+  })
+
+  it('can rule out a full clear', () => {
+    // Both moves reach into the top row while the other bottom block is still there
+    const grid = [[7, 4, 0], [3, 0, 6]]
+    expect(countRemainingBlocks(solvePuzzle(grid)[1].gridAfter)).toBe(0)
+    expect(solvePuzzle(grid, { clearOrder: 'bottomUp' })).toEqual([])
+  })
+})
+
+describe('rectanglePenalty', () => {
+  // The square on the left and the run along the top both clear 4 blocks and share the 3, so only one is played
+  const grid = [[2, 3, 2, 4, 1], [3, 2, 0, 0, 0]]
+
+  it('swaps a rectangle for a line worth as much', () => {
+    const plain = solvePuzzle(grid)
+    const penalized = solvePuzzle(grid, { rectanglePenalty: 0.5 })
+
+    expect(plain.map(step => getSelectionType(step.cells))).toEqual(['Rectangle'])
+    expect(penalized.map(step => getSelectionType(step.cells))).toEqual(['Horizontal'])
+    expect(totalScore(penalized)).toBe(totalScore(plain))
+  })
+})
+
+describe('refillQueue', () => {
+  const grid = [[0, 0], [1, 9]]
+
+  it('plays refilled blocks in later moves', () => {
+    const steps = solvePuzzle(grid, { gravity: 'down', refillQueue: [3, 7, 5] })
+    expect(steps.map(step => step.cells.map(c => c.value))).toEqual([[1, 9], [3, 7]])
+    expect(steps[1].gridAfter).toEqual([[0, 0], [5, 0]])
+  })
+
+  it('ends once the board runs out without a queue', () => {
+    expect(solvePuzzle(grid, { gravity: 'down' })).toHaveLength(1)
+  })
+})
+
+describe('isTargetFeasible', () => {
+  const sevens = [[7, 7, 7], [7, 7, 7]]
+
+  it('flags a board whose values can never add up to the target', () => {
+    expect(isTargetFeasible(sevens, { horizontalTarget: 10, verticalTarget: 10, rectangleTarget: 10 })).toBe(false)
+  })
+
+  it('accepts a target that a group of the values reaches', () => {
+    expect(isTargetFeasible(sevens, { horizontalTarget: 14 })).toBe(true)
+  })
+
+  it('skips fractional targets instead of throwing', () => {
+    const options = { horizontalTarget: 10.5, verticalTarget: 10.5, rectangleTarget: 10.5 }
+    expect(isTargetFeasible([[5, 5, 1]], options)).toBe(false)
+    expect(isTargetFeasible([[5, 5, 1]], { ...options, rectangleTarget: 10 })).toBe(true)
+  })
+})
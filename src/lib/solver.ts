@@ -1,68 +1,173 @@
-import type { Cell, Step } from '../types'
+import type {
+  AffectedLines,
+  BoundingBox,
+  Cell,
+  ClearOrder,
+  ComboShape,
+  Combination,
+  CostEstimate,
+  Diagnostics,
+  GameState,
+  MoveQuality,
+  NearMiss,
+  RestartSummary,
+  SearchTreeNode,
+  SolverOptions,
+  SolverStyle,
+  Step,
+} from '../types'
+import {
+  applyGravity,
+  boundingBoxesOverlap,
+  countRemainingBlocks,
+  createRng,
+  getBoardSymmetry,
+  getBoundingBox,
+  getBoundingBoxArea,
+  getCombinationPositions,
+  getCombinationSum,
+  getGravityLanes,
+  getGridKey,
+  getSelectionType,
+  HIDDEN_CELL,
+  matchesValueConstraint,
+  mergeBoundingBoxes,
+  reverseSteps,
+  shiftCells,
+  shiftSteps,
+  validateGrid,
+} from './utils'
 
-// Generate all subsets of an array
-const getAllSubsets = <T,>(arr: T[]): T[][] => {
-  const subsets: T[][] = [[]]
-  
-  for (const item of arr) {
-    const newSubsets = subsets.map(subset => [...subset, item])
-    subsets.push(...newSubsets)
+// Cells outside the playable region act as walls
+type WallCheck = (row: number, col: number) => boolean
+
+const noWalls: WallCheck = () => false
+
+// Cells outside the playable mask, frozen rows and hidden cells all block selections
+const getWallCheck = (grid: number[][], options: SolverOptions): WallCheck => {
+  const { playableMask, frozenRows = 0 } = options
+  const hasHidden = grid.some(row => row.includes(HIDDEN_CELL))
+  if (!playableMask && frozenRows <= 0 && !hasHidden) return noWalls
+
+  return (row, col) => {
+    return row < frozenRows || playableMask?.[row]?.[col] === false || grid[row]?.[col] === HIDDEN_CELL
   }
-  
-  return subsets
 }
 
-// Check if horizontal selection is valid (blocks between selected cells must be empty or selected)
-const isValidHorizontalSelection = (cells: Cell[], row: number[]): boolean => {
-  if (cells.length === 0) return false
-  if (cells.length === 1) return true
+// Find every selection along a row or column whose values add up to the target and that forms a valid run:
+// unselected blocks between the ends can be skipped up to maxGaps times and walls can never be crossed.
+// With wrap the run may continue past the last position back to the first. Runs are grown one block at a time
+// from each starting block, so only valid runs are visited and long lines can't blow up into 2^n subsets.
+const findRunsWithSum = (
+  length: number,
+  cellAt: (pos: number) => Cell,
+  isWallAt: (pos: number) => boolean,
+  target: number,
+  maxGaps = 0,
+  wrap = false
+): Cell[][] => {
+  if (target <= 0) return []
 
-  const cols = cells.map(c => c.col).sort((a, b) => a - b)
-  const minCol = cols[0]
-  const maxCol = cols[cols.length - 1]
-
-  for (let col = minCol; col <= maxCol; col++) {
-    const value = row[col]
-    const isSelected = cells.some(c => c.col === col)
-    
-    if (!isSelected && value !== 0) {
-      return false
-    }
+  const blocks = Array.from({ length }, (_, pos) => pos).filter(pos => cellAt(pos).value > 0 && !isWallAt(pos))
+  // With wrap, a second lap of the line lets runs continue from the last block back to the first
+  const sequence = wrap ? [...blocks, ...blocks.map(pos => pos + length)] : blocks
+  const wallsBefore = [0]
+  for (let pos = 0; pos < (wrap ? 2 * length : length); pos++) {
+    wallsBefore.push(wallsBefore[pos] + Number(isWallAt(pos % length)))
   }
 
-  return true
-}
+  const runs: Cell[][] = []
+  const current: Cell[] = []
 
-// Check if vertical selection is valid (blocks between selected cells must be empty or selected)
-const isValidVerticalSelection = (cells: Cell[], grid: number[][], col: number): boolean => {
-  if (cells.length === 0) return false
-  if (cells.length === 1) return true
+  const extend = (first: number, last: number, sum: number, gapsLeft: number) => {
+    if (sum === target) {
+      runs.push([...current].sort((a, b) => a.row - b.row || a.col - b.col))
+      return
+    }
 
-  const rows = cells.map(c => c.row).sort((a, b) => a - b)
-  const minRow = rows[0]
-  const maxRow = rows[rows.length - 1]
+    // Only a lap's worth of blocks fits in one run, and each skipped block uses up a gap
+    const limit = Math.min(first + blocks.length, last + gapsLeft + 2)
+    for (let next = last + 1; next < limit; next++) {
+      // A wall between the two blocks stays in the way of every block further along
+      if (wallsBefore[sequence[next]] !== wallsBefore[sequence[last] + 1]) break
 
-  for (let row = minRow; row <= maxRow; row++) {
-    const value = grid[row][col]
-    const isSelected = cells.some(c => c.row === row)
-    
-    if (!isSelected && value !== 0) {
-      return false
+      const cell = cellAt(sequence[next] % length)
+      if (sum + cell.value > target) continue
+
+      current.push(cell)
+      extend(first, next, sum + cell.value, gapsLeft - (next - last - 1))
+      current.pop()
     }
   }
 
-  return true
+  blocks.forEach((pos, idx) => {
+    const cell = cellAt(pos)
+    if (cell.value > target) return
+
+    current.push(cell)
+    extend(idx, idx, cell.value, maxGaps)
+    current.pop()
+  })
+
+  return runs
+}
+
+// A divider at index d sits between positions d - 1 and d, so a selection crosses it
+// when it has positions on both sides
+const crossesDivider = (positions: number[], dividers: number[]): boolean => {
+  if (dividers.length === 0) return false
+  const min = Math.min(...positions)
+  const max = Math.max(...positions)
+  return dividers.some(d => min < d && d <= max)
 }
 
-// Find all valid combinations that sum to 10 (horizontal, vertical, and rectangular)
-export const findValidCombinations = (grid: number[][]): Cell[][] => {
+// Find all valid combinations that sum to their shape's target, 10 by default (horizontal, vertical, and rectangular).
+// Works in 0-based coordinates throughout; findValidCombinations is the entry point that honors oneIndexed.
+const enumerateCombinations = (grid: number[][], options: SolverOptions): Combination[] => {
+  const {
+    rectangleMode = 'sparse',
+    perGenerationBudgetMs,
+    maxCombinations = Infinity,
+    valueConstraint,
+    requireValue,
+    maxGaps = 0,
+    shapeSchedule,
+    wrapHorizontal = false,
+    wrapVertical = false,
+    rowDividers = [],
+    colDividers = [],
+    horizontalTarget = 10,
+    verticalTarget = 10,
+    rectangleTarget = 10,
+  } = options
+  const requiredValue = Array.isArray(requireValue) ? requireValue[0] : requireValue
+  const allowedShape = shapeSchedule?.[0]
+  const allows = (...shapes: ComboShape[]) => allowedShape === undefined || shapes.includes(allowedShape)
   const rows = grid.length
   const cols = grid[0]?.length || 0
-  const combinations: Cell[][] = []
+  const combinations: Combination[] = []
   const seen = new Set<string>()
+  const isWall = getWallCheck(grid, options)
+
+  // Stop enumerating once enough moves were found, or once the time slice for this generation is used up
+  // and at least one move was found, keeping what was found. An empty result always means no move exists,
+  // never that the slice ran out.
+  const deadline = perGenerationBudgetMs === undefined ? Infinity : performance.now() + perGenerationBudgetMs
+  const isOverBudget = () => {
+    if (combinations.length >= maxCombinations) return true
+    return combinations.length > 0 && deadline !== Infinity && performance.now() > deadline
+  }
 
+  // The same blocks can be found more than once, e.g. as a line and as a sparse rectangle around it,
+  // so moves are deduplicated on their sorted row-major cell indices
   const addCombination = (cells: Cell[]) => {
-    const key = cells.map(c => `${c.row}-${c.col}`).sort().join('|')
+    if (combinations.length >= maxCombinations) return
+    if (requiredValue != null && !cells.some(c => c.value === requiredValue)) return
+    if (allowedShape !== undefined && getSelectionType(cells) !== allowedShape) return
+    if (valueConstraint && !matchesValueConstraint(cells, valueConstraint)) return
+    if (crossesDivider(cells.map(c => c.row), rowDividers) || crossesDivider(cells.map(c => c.col), colDividers)) return
+
+    const key = cells.map(c => c.row * cols + c.col).sort((a, b) => a - b).join(',')
     if (!seen.has(key)) {
       seen.add(key)
       combinations.push(cells)
@@ -70,51 +175,40 @@ export const findValidCombinations = (grid: number[][]): Cell[][] => {
   }
 
   // 1. HORIZONTAL: For each row, find all valid horizontal selections
-  for (let row = 0; row < rows; row++) {
-    const rowCells = grid[row]
-      .map((value, col) => ({ row, col, value }))
-      .filter(cell => cell.value > 0)
+  for (let row = 0; allows('Horizontal', 'Single') && row < rows; row++) {
+    if (isOverBudget()) return combinations
 
-    const subsets = getAllSubsets(rowCells)
-    
-    for (const subset of subsets) {
-      if (subset.length === 0) continue
-      
-      const sum = subset.reduce((acc, cell) => acc + cell.value, 0)
-      if (sum !== 10) continue
-      
-      if (isValidHorizontalSelection(subset, grid[row])) {
-        addCombination(subset)
-      }
-    }
+    const runs = findRunsWithSum(
+      cols,
+      col => ({ row, col, value: grid[row][col] }),
+      col => isWall(row, col),
+      horizontalTarget,
+      maxGaps,
+      wrapHorizontal
+    )
+    runs.forEach(addCombination)
   }
 
   // 2. VERTICAL: For each column, find all valid vertical selections
-  for (let col = 0; col < cols; col++) {
-    const colCells: Cell[] = []
-    for (let row = 0; row < rows; row++) {
-      if (grid[row][col] > 0) {
-        colCells.push({ row, col, value: grid[row][col] })
-      }
-    }
+  for (let col = 0; allows('Vertical', 'Single') && col < cols; col++) {
+    if (isOverBudget()) return combinations
 
-    const subsets = getAllSubsets(colCells)
-    
-    for (const subset of subsets) {
-      if (subset.length === 0) continue
-      
-      const sum = subset.reduce((acc, cell) => acc + cell.value, 0)
-      if (sum !== 10) continue
-      
-      if (isValidVerticalSelection(subset, grid, col)) {
-        addCombination(subset)
-      }
-    }
+    const runs = findRunsWithSum(
+      rows,
+      row => ({ row, col, value: grid[row][col] }),
+      row => isWall(row, col),
+      verticalTarget,
+      maxGaps,
+      wrapVertical
+    )
+    runs.forEach(addCombination)
   }
 
   // 3. RECTANGULAR: Find all valid rectangular selections
-  for (let minRow = 0; minRow < rows; minRow++) {
+  for (let minRow = 0; allows('Rectangle') && minRow < rows; minRow++) {
     for (let minCol = 0; minCol < cols; minCol++) {
+      if (isOverBudget()) return combinations
+
       for (let maxRow = minRow; maxRow < rows; maxRow++) {
         for (let maxCol = minCol; maxCol < cols; maxCol++) {
           // Skip single row or single column (already covered above)
@@ -122,21 +216,35 @@ export const findValidCombinations = (grid: number[][]): Cell[][] => {
           
           // Get all non-empty cells in this rectangle
           const rectCells: Cell[] = []
+          let hasEmptyCell = false
+          let hasWall = false
           
           for (let r = minRow; r <= maxRow; r++) {
             for (let c = minCol; c <= maxCol; c++) {
               const value = grid[r][c]
-              if (value > 0) {
+              if (isWall(r, c)) {
+                hasWall = true
+              } else if (value > 0) {
                 rectCells.push({ row: r, col: c, value })
+              } else {
+                hasEmptyCell = true
               }
             }
           }
           
+          // Widening the rectangle keeps the wall inside it
+          if (hasWall) break
           if (rectCells.length === 0) continue
           
           // For rectangular selection, all cells in the rectangle must be selected
           const sum = rectCells.reduce((acc, cell) => acc + cell.value, 0)
-          if (sum === 10) {
+          // Widening the rectangle can only add blocks, so stop once the sum overshoots
+          if (sum > rectangleTarget) break
+          
+          // Solid rectangles must be completely filled, sparse ones may span empty cells
+          if (rectangleMode === 'solid' && hasEmptyCell) continue
+          
+          if (sum === rectangleTarget) {
             addCombination(rectCells)
           }
         }
@@ -147,42 +255,1024 @@ export const findValidCombinations = (grid: number[][]): Cell[][] => {
   return combinations
 }
 
-// Solve the puzzle - find optimal sequence of moves
-export const solvePuzzle = (initialGrid: number[][]): Step[] => {
-  const steps: Step[] = []
-  let currentGrid = initialGrid.map(row => [...row])
+// Every valid combination on the board, in the coordinates the options ask for
+export const findValidCombinations = (grid: number[][], options: SolverOptions = {}): Combination[] => {
+  const combinations = enumerateCombinations(grid, toZeroIndexedOptions(options))
+  return options.oneIndexed ? combinations.map(cells => shiftCells(cells, 1)) : combinations
+}
+
+// Find at most maxMoves legal combinations for a quick hint, stopping the enumeration early.
+// Lines are enumerated before rectangles, so the cheapest moves to find come first.
+export const findMovesBounded = (grid: number[][], maxMoves: number, options: SolverOptions = {}): Combination[] => {
+  return findValidCombinations(grid, { ...options, maxCombinations: maxMoves })
+}
+
+// Biggest solid rectangle that clears in one move (summing to rectangleTarget), or null if there is none
+export const findLargestRectangleClear = (grid: number[][], options: SolverOptions = {}): Combination | null => {
+  const rectangles = findValidCombinations(grid, {
+    ...options,
+    rectangleMode: 'solid',
+    shapeSchedule: ['Rectangle'],
+  })
+  return rectangles.reduce<Combination | null>((best, cells) => {
+    return !best || cells.length > best.length ? cells : best
+  }, null)
+}
+
+// Highest-scoring legal horizontal combination in each row, or null for rows without one
+export const getBestLinePerRow = (grid: number[][], options: SolverOptions = {}): (Combination | null)[] => {
+  const internalOptions = toZeroIndexedOptions(options)
+  const { maxGaps = 0, wrapHorizontal = false, colDividers = [], horizontalTarget = 10 } = internalOptions
+  const isWall = getWallCheck(grid, internalOptions)
+
+  return grid.map((values, row) => {
+    const runs = findRunsWithSum(
+      values.length,
+      col => ({ row, col, value: values[col] }),
+      col => isWall(row, col),
+      horizontalTarget,
+      maxGaps,
+      wrapHorizontal
+    )
+
+    let best: Combination | null = null
+    for (const subset of runs) {
+      if (crossesDivider(subset.map(c => c.col), colDividers)) continue
+      if (!best || calculateMoveScore(subset, options) > calculateMoveScore(best, options)) {
+        best = subset
+      }
+    }
+    return best && options.oneIndexed ? shiftCells(best, 1) : best
+  })
+}
+
+// Contiguous row and column groups whose sum misses the target by at most slack, for hinting at
+// "almost" moves. Each group carries its delta from the target (negative when it falls short).
+export const findNearMissMoves = (
+  grid: number[][],
+  target = 10,
+  slack = 1,
+  options: SolverOptions = {}
+): NearMiss[] => {
+  const isWall = getWallCheck(grid, options)
+  const cols = grid[0]?.length || 0
+  const offset = options.oneIndexed ? 1 : 0
+  const nearMisses: NearMiss[] = []
+  const seen = new Set<string>()
+
+  const addNearMisses = (length: number, cellAt: (pos: number) => Cell, isWallAt: (pos: number) => boolean) => {
+    for (let delta = -slack; delta <= slack; delta++) {
+      if (delta === 0) continue
+
+      for (const cells of findRunsWithSum(length, cellAt, isWallAt, target + delta)) {
+        const key = cells.map(c => c.row * cols + c.col).sort((a, b) => a - b).join(',')
+        if (seen.has(key)) continue
+        seen.add(key)
+        nearMisses.push({ cells: shiftCells(cells, offset), delta })
+      }
+    }
+  }
+
+  grid.forEach((values, row) => {
+    addNearMisses(values.length, col => ({ row, col, value: values[col] }), col => isWall(row, col))
+  })
+
+  for (let col = 0; col < cols; col++) {
+    addNearMisses(grid.length, row => ({ row, col, value: grid[row][col] }), row => isWall(row, col))
+  }
+
+  return nearMisses
+}
+
+// Score awarded for clearing a combination (one point per block)
+export const calculateMoveScore = (cells: Combination, options: SolverOptions = {}): number => {
+  const { scoreSingleCellClears = true } = options
+  if (cells.length === 1 && !scoreSingleCellClears) return 0
+  return cells.length
+}
+
+// Score for a move played after `chain` consecutive large clears. With a chainBonus, every large clear
+// (at least chainMinSize blocks) in an unbroken run earns an extra chainBonus multiple of its base score.
+export const calculateChainedMoveScore = (cells: Combination, chain: number, options: SolverOptions = {}): number => {
+  const { chainBonus = 0, chainMinSize = 4 } = options
+  const score = calculateMoveScore(cells, options)
+  if (chainBonus === 0 || cells.length < chainMinSize) return score
+  return score * (1 + chainBonus * chain)
+}
+
+// Length of the run of large clears after playing a move; a smaller clear breaks it
+export const getChainAfter = (cells: Combination, chain: number, options: SolverOptions = {}): number => {
+  const { chainMinSize = 4 } = options
+  return cells.length >= chainMinSize ? chain + 1 : 0
+}
+
+// Cells that hold their place under gravity: walls outside the playable mask and frozen rows
+const getFixedCheck = (options: SolverOptions) => {
+  const { playableMask, frozenRows = 0 } = options
+  return (row: number, col: number) => row < frozenRows || playableMask?.[row]?.[col] === false
+}
+
+// Clear a combination from the grid, returning a new grid with gravity applied along with how many blocks
+// the clear revealed
+const clearCombination = (
+  grid: number[][],
+  cells: Combination,
+  options: SolverOptions
+): { grid: number[][]; revealed: number } => {
+  const { gravity = 'none', hiddenValues } = options
+  const newGrid = grid.map(row => [...row])
+  for (const cell of cells) {
+    newGrid[cell.row][cell.col] = 0
+  }
+  const revealed = revealHiddenCells(newGrid, cells, hiddenValues)
+  return { grid: applyGravity(newGrid, gravity, getFixedCheck(options)), revealed }
+}
+
+// Clear a combination from the grid, returning a new grid with gravity applied
+export const applyMove = (grid: number[][], cells: Combination, options: SolverOptions = {}): number[][] => {
+  return clearCombination(grid, cells, options).grid
+}
+
+// Turn hidden cells next to cleared blocks into their real values, in place, and return how many of them
+// turned out to be blocks. A hidden cell without a known value is revealed as empty.
+export const revealHiddenCells = (grid: number[][], cleared: Combination, hiddenValues?: number[][]): number => {
+  let revealed = 0
+  for (const { row, col } of cleared) {
+    for (const [r, c] of [[row - 1, col], [row + 1, col], [row, col - 1], [row, col + 1]]) {
+      if (grid[r]?.[c] === HIDDEN_CELL) {
+        grid[r][c] = hiddenValues?.[r]?.[c] ?? 0
+        if (grid[r][c] > 0) revealed++
+      }
+    }
+  }
+  return revealed
+}
+
+// Refill the blocks of a clear from the refill queue, in place, starting at queue index `used`, and return how
+// many queue values are used afterwards. Without gravity each cleared cell is refilled where it was; with gravity
+// the new blocks settle on top of the remaining ones in each lane the clear touched.
+const refillCleared = (grid: number[][], cleared: Combination, used: number, options: SolverOptions): number => {
+  const { refillQueue: queue = [], gravity = 'none' } = options
+  let next = used
+  const targets: [number, number][] = []
+
+  if (gravity === 'none') {
+    targets.push(...getCombinationPositions(cleared))
+  } else {
+    const inMove = new Set(cleared.map(c => `${c.row}-${c.col}`))
+    for (const lane of getGravityLanes(grid.length, grid[0]?.length ?? 0, gravity, getFixedCheck(options))) {
+      const count = lane.filter(([row, col]) => inMove.has(`${row}-${col}`)).length
+      const settled = lane.filter(([row, col]) => grid[row][col] !== 0).length
+      targets.push(...lane.slice(settled, settled + count))
+    }
+  }
+
+  for (const [row, col] of targets) {
+    if (next >= queue.length) break
+    grid[row][col] = queue[next++]
+  }
+  return next
+}
+
+// Resolve options that can vary per move into the values used for a given move
+const getMoveOptions = (options: SolverOptions, moveIndex: number): SolverOptions => {
+  const { requireValue, shapeSchedule } = options
+  const moveOptions = { ...options }
+
+  if (Array.isArray(requireValue)) {
+    moveOptions.requireValue = requireValue[moveIndex] ?? undefined
+  }
+  if (shapeSchedule && shapeSchedule.length > 0) {
+    moveOptions.shapeSchedule = [shapeSchedule[moveIndex % shapeSchedule.length]]
+  }
+
+  return moveOptions
+}
+
+// Penalty (in blocks) for a move whose bounding box overlaps the previous move's
+const OVERLAP_PENALTY = 1
+
+// Weight presets for non-expert users; explicitly set options take precedence
+const STYLE_PRESETS: Record<SolverStyle, SolverOptions> = {
+  // Maximize immediate clears: never trade blocks for anything else, and of two equally big clears
+  // take the multi-row rectangle
+  aggressive: { rectanglePenalty: -0.5 },
+  // Clear boundary blocks early, even over a slightly bigger clear, so they don't end up stranded
+  efficient: { edgeFirstWeight: 1 },
+  // The default weights
+  balanced: {},
+}
+
+// Rank a candidate move for the greedy solver; higher is better.
+// This is the solver's internal priority, not the score reported for the move.
+const evaluateMove = (cells: Combination, context: GameState, options: SolverOptions): number => {
+  const weights = options.style ? { ...STYLE_PRESETS[options.style], ...options } : options
+  const {
+    objective = 'score',
+    consecutiveOverlap = 'allow',
+    centralityBonus = 0,
+    edgeFirstWeight = 0,
+    compactnessWeight = 0,
+    rectanglePenalty = 0,
+  } = weights
+  const height = context.grid.length
+  const width = context.grid[0]?.length ?? 0
+
+  // Prefer clearing more blocks, or fewer blocks to leave room for more moves, unless the caller scores moves
+  let value = options.scoreMove
+    ? options.scoreMove(cells, context.grid)
+    : objective === 'maxMoves' ? -cells.length : calculateChainedMoveScore(cells, context.chain, options)
+
+  // Reward moves centered near the middle of the board, scaled down with distance
+  if (centralityBonus !== 0) {
+    const centerRow = (height - 1) / 2
+    const centerCol = (width - 1) / 2
+    const centroidRow = cells.reduce((acc, c) => acc + c.row, 0) / cells.length
+    const centroidCol = cells.reduce((acc, c) => acc + c.col, 0) / cells.length
+    const distance = Math.hypot(centroidRow - centerRow, centroidCol - centerCol)
+    value += centralityBonus / (1 + distance)
+  }
+
+  // Reward clearing boundary blocks before they get stranded; corners touch two sides and count twice
+  if (edgeFirstWeight !== 0) {
+    const edgeSides = cells.reduce((acc, c) => {
+      return acc +
+        Number(c.row === 0) + Number(c.row === height - 1) +
+        Number(c.col === 0) + Number(c.col === width - 1)
+    }, 0)
+    value += edgeFirstWeight * edgeSides
+  }
+
+  // Nudge towards line clears when they are about as good as a rectangle
+  if (rectanglePenalty !== 0 && getSelectionType(cells) === 'Rectangle') {
+    value -= rectanglePenalty
+  }
+
+  // Penalize how much the move grows the area of the region the solution has touched so far
+  if (compactnessWeight !== 0 && context.activity) {
+    const grown = mergeBoundingBoxes(context.activity, getBoundingBox(cells))
+    value -= compactnessWeight * (getBoundingBoxArea(grown) - getBoundingBoxArea(context.activity))
+  }
+
+  if (
+    consecutiveOverlap === 'penalize' &&
+    context.previous &&
+    boundingBoxesOverlap(getBoundingBox(cells), getBoundingBox(context.previous))
+  ) {
+    value -= OVERLAP_PENALTY
+  }
+
+  return value
+}
+
+// Budget left after paying for a move and collecting the reward for each block it clears
+const getBudgetAfter = (budget: number, cells: Combination, options: SolverOptions): number => {
+  const { moveCost = 0, clearReward = 0 } = options
+  return budget - moveCost + clearReward * cells.length
+}
+
+// A must-clear cell is stranded once it still holds a block that no combination can reach
+const strandsMustClear = (grid: number[][], options: SolverOptions): boolean => {
+  const { mustClear = [] } = options
+  const pending = mustClear.filter(([row, col]) => (grid[row]?.[col] ?? 0) > 0)
+  if (pending.length === 0) return false
+
+  const reachable = new Set(
+    enumerateCombinations(grid, { ...options, requireValue: undefined, shapeSchedule: undefined })
+      .flatMap(cells => cells.map(c => `${c.row}-${c.col}`))
+  )
+  return pending.some(([row, col]) => !reachable.has(`${row}-${col}`))
+}
+
+// Check that a move only clears a row once every block beyond it (below for bottomUp, above for topDown)
+// is gone or cleared by the same move
+const respectsClearOrder = (grid: number[][], cells: Combination, clearOrder: ClearOrder): boolean => {
+  if (clearOrder === 'any') return true
+
+  const inMove = new Set(cells.map(c => `${c.row}-${c.col}`))
+  const { minRow, maxRow } = getBoundingBox(cells)
+  const isBeyond = (row: number) => clearOrder === 'bottomUp' ? row > minRow : row < maxRow
+
+  return grid.every((values, row) => {
+    return !isBeyond(row) || values.every((value, col) => value <= 0 || inMove.has(`${row}-${col}`))
+  })
+}
+
+// Drop candidates that break move-to-move rules
+const filterCandidates = (combinations: Combination[], state: GameState, options: SolverOptions): Combination[] => {
+  const {
+    consecutiveOverlap = 'allow',
+    forbidRepeatShape = false,
+    clearOrder = 'any',
+    maxTotalCleared = Infinity,
+  } = options
+  const { grid, previous, budget, cleared } = state
+  let candidates = combinations.filter(cells => {
+    return getBudgetAfter(budget, cells, options) >= 0
+      && cleared + cells.length <= maxTotalCleared
+      && respectsClearOrder(grid, cells, clearOrder)
+  })
+
+  if (consecutiveOverlap === 'forbid' && previous) {
+    const previousBox = getBoundingBox(previous)
+    candidates = candidates.filter(cells => !boundingBoxesOverlap(getBoundingBox(cells), previousBox))
+  }
+
+  if (forbidRepeatShape && previous) {
+    const previousShape = getSelectionType(previous)
+    candidates = candidates.filter(cells => getSelectionType(cells) !== previousShape)
+  }
+
+  return candidates
+}
+
+// Mirror symmetries of the board that the rules keep too. A flip only counts when it also maps the playable
+// cells, frozen rows, dividers, hidden values and pre-cleared or must-clear cells onto themselves, and the
+// direction the rules play in (gravity, clear order) doesn't change under it. Refills land in a fixed order
+// and a custom scoreMove or maxCombinations can tell mirrored moves apart, so those rule out both flips.
+export const getRuleSymmetry = (
+  grid: number[][],
+  options: SolverOptions
+): { leftRight: boolean; topBottom: boolean } => {
+  const {
+    gravity = 'none',
+    clearOrder = 'any',
+    frozenRows = 0,
+    playableMask,
+    hiddenValues,
+    preCleared = [],
+    mustClear = [],
+    rowDividers = [],
+    colDividers = [],
+  } = options
+  if (options.refillQueue?.length || options.scoreMove || options.maxCombinations !== undefined) {
+    return { leftRight: false, topBottom: false }
+  }
+
+  const { leftRight, topBottom } = getBoardSymmetry(grid)
+  const height = grid.length
+  const width = grid[0]?.length ?? 0
+  const keepsRules = (flipRows: boolean, flipCols: boolean) => {
+    const flip = (row: number, col: number): [number, number] => {
+      return [flipRows ? height - 1 - row : row, flipCols ? width - 1 - col : col]
+    }
+    const keepsCells = grid.every((row, r) => row.every((_, c) => {
+      const [mirrorRow, mirrorCol] = flip(r, c)
+      return (playableMask?.[r]?.[c] !== false) === (playableMask?.[mirrorRow]?.[mirrorCol] !== false)
+        && hiddenValues?.[r]?.[c] === hiddenValues?.[mirrorRow]?.[mirrorCol]
+    }))
+    const keepsPositions = (positions: [number, number][]) => {
+      const keys = new Set(positions.map(([row, col]) => `${row}-${col}`))
+      return positions.every(([row, col]) => keys.has(flip(row, col).join('-')))
+    }
+    const keepsDividers = (dividers: number[], size: number, flipped: boolean) => {
+      return !flipped || dividers.every(d => dividers.includes(size - d))
+    }
+
+    return keepsCells
+      && keepsPositions(preCleared)
+      && keepsPositions(mustClear)
+      && keepsDividers(rowDividers, height, flipRows)
+      && keepsDividers(colDividers, width, flipCols)
+      && (!flipRows || (frozenRows <= 0 && clearOrder === 'any' && gravity !== 'down'))
+      && (!flipCols || gravity !== 'left')
+  }
+
+  return { leftRight: leftRight && keepsRules(false, true), topBottom: topBottom && keepsRules(true, false) }
+}
+
+// On a board that is mirror-symmetric along with its rules, every first move has an equivalent mirrored twin,
+// so only keep one of each pair. This only narrows the greedy search; the mirrored twin is still a legal move.
+const dropMirroredFirstMoves = (candidates: Combination[], state: GameState, options: SolverOptions): Combination[] => {
+  if (!options.exploitSymmetry || state.previous) return candidates
+
+  const { grid } = state
+  const { leftRight, topBottom } = getRuleSymmetry(grid, options)
+  const height = grid.length
+  const width = grid[0]?.length ?? 0
+  const toKey = (cells: Combination) => getCombinationPositions(cells).join('|')
+  const mirror = (cells: Combination, flipRows: boolean, flipCols: boolean) => cells.map(c => ({
+    ...c,
+    row: flipRows ? height - 1 - c.row : c.row,
+    col: flipCols ? width - 1 - c.col : c.col,
+  }))
+
+  let kept = candidates
+  if (leftRight) kept = kept.filter(cells => toKey(cells) <= toKey(mirror(cells, false, true)))
+  if (topBottom) kept = kept.filter(cells => toKey(cells) <= toKey(mirror(cells, true, false)))
+  return kept
+}
+
+// Skip moves that would strand a must-clear cell. If every move does, the objective is
+// already lost, so keep playing normally instead of ending the game.
+export const avoidStrandingMustClear = (
+  candidates: Combination[],
+  grid: number[][],
+  options: SolverOptions
+): Combination[] => {
+  if (!options.mustClear?.length) return candidates
+
+  const safe = candidates.filter(cells => !strandsMustClear(applyMove(grid, cells, options), options))
+  return safe.length > 0 ? safe : candidates
+}
+
+// Copy the input grid and apply any hypothetical edits from the options, leaving the caller's grid untouched
+const prepareGrid = (initialGrid: number[][], options: SolverOptions): number[][] => {
+  const error = validateGrid(initialGrid, options.hiddenValues !== undefined)
+  if (error) throw new Error(error)
+
+  const { preCleared = [] } = options
+  const grid = initialGrid.map(row => [...row])
+  for (const [row, col] of preCleared) {
+    if (grid[row]?.[col] !== undefined) {
+      grid[row][col] = 0
+    }
+  }
+  return grid
+}
+
+// State before the first move on an already prepared grid
+const getInitialState = (grid: number[][], options: SolverOptions): GameState => {
+  const { startingBudget = 0 } = options
+  return {
+    grid,
+    moveIndex: 0,
+    budget: startingBudget,
+    chain: 0,
+    cleared: 0,
+    remaining: countRemainingBlocks(grid),
+    refillUsed: 0,
+  }
+}
+
+// Start a game on a copy of the grid with the hypothetical edits from the options applied.
+// Throws on a malformed grid, like the solve functions.
+export const startGame = (initialGrid: number[][], options: SolverOptions = {}): GameState => {
+  return getInitialState(prepareGrid(initialGrid, options), options)
+}
+
+// Every move the rules allow in a game state: the combinations on the board under this move's options,
+// minus those breaking a move-to-move rule. The solver and the verifier both go through this check.
+export const getLegalMoves = (state: GameState, options: SolverOptions = {}): Combination[] => {
+  const combinations = enumerateCombinations(state.grid, getMoveOptions(options, state.moveIndex))
+  return filterCandidates(combinations, state, options)
+}
+
+// Whether a move scores at least minMoveScore, the least a move has to be worth to be played
+export const meetsMinMoveScore = (cells: Combination, state: GameState, options: SolverOptions = {}): boolean => {
+  const { minMoveScore } = options
+  return minMoveScore === undefined || calculateChainedMoveScore(cells, state.chain, options) >= minMoveScore
+}
+
+// Play a move and return the resulting game state. The move scores
+// calculateChainedMoveScore(cells, state.chain, options).
+export const playMove = (state: GameState, cells: Combination, options: SolverOptions = {}): GameState => {
+  const { grid, revealed } = clearCombination(state.grid, cells, options)
+  const moveBox = getBoundingBox(cells)
+  const refillUsed = options.refillQueue ? refillCleared(grid, cells, state.refillUsed, options) : state.refillUsed
+  const refilled = options.refillQueue?.slice(state.refillUsed, refillUsed).filter(value => value > 0).length ?? 0
+
+  return {
+    grid,
+    moveIndex: state.moveIndex + 1,
+    previous: cells,
+    budget: getBudgetAfter(state.budget, cells, options),
+    activity: state.activity ? mergeBoundingBoxes(state.activity, moveBox) : moveBox,
+    chain: getChainAfter(cells, state.chain, options),
+    cleared: state.cleared + cells.length,
+    remaining: state.remaining - cells.length + revealed + refilled,
+    refillUsed,
+  }
+}
+
+// A candidate move with the priority the greedy solver gave it
+interface RankedCombination {
+  cells: Combination
+  value: number
+}
+
+// Called once per move with the board before the move, every candidate, the chosen combination
+// and the candidates ordered best first
+type GenerationCallback = (
+  grid: number[][],
+  combinations: Combination[],
+  chosen: Combination,
+  ranked: RankedCombination[]
+) => void
+
+// Greedily play moves until no valid combination is left, yielding each step as soon as it is chosen
+function* iterateGreedy(
+  initialGrid: number[][],
+  options: SolverOptions,
+  onGeneration?: GenerationCallback
+): Generator<Step> {
+  yield* iterateGreedyFrom(getInitialState(initialGrid.map(row => [...row]), options), options, onGeneration)
+}
+
+// Keep playing greedily from a game state that is already under way
+function* iterateGreedyFrom(
+  start: GameState,
+  options: SolverOptions,
+  onGeneration?: GenerationCallback
+): Generator<Step> {
+  const { recordChoices = false, tieBreakSeed } = options
+  const tieBreaker = tieBreakSeed === undefined ? undefined : createRng(tieBreakSeed)
+  let state = start
 
   while (true) {
-    const combinations = findValidCombinations(currentGrid)
-    if (combinations.length === 0) break
-
-    // Prioritize combinations that:
-    // 1. Clear more cells
-    // 2. Clear cells that might block others
-    const sortedCombinations = [...combinations].sort((a, b) => {
-      // Prefer clearing more blocks
-      if (b.length !== a.length) return b.length - a.length
-      // Then prefer leftmost selections (arbitrary tiebreaker)
-      return a[0].col - b[0].col
-    })
-
-    const bestCombination = sortedCombinations[0]
+    const legalMoves = getLegalMoves(state, options)
+    const candidates = avoidStrandingMustClear(dropMirroredFirstMoves(legalMoves, state, options), state.grid, options)
+    if (candidates.length === 0) return
+
+    // Pass instead of taking a move that isn't worth enough, which ends the game
+    const combinations = candidates.filter(cells => meetsMinMoveScore(cells, state, options))
+    const choices = recordChoices ? { choicesBefore: candidates.length } : {}
+    if (combinations.length === 0) {
+      yield {
+        cells: [],
+        sum: 0,
+        score: 0,
+        gridAfter: state.grid,
+        ...choices
+      }
+      return
+    }
+
+    // Prioritize combinations with the best evaluation, then prefer leftmost selections
+    // (arbitrary tiebreaker) or a seeded random pick among the tied ones
+    const rankedCombinations: RankedCombination[] = combinations
+      .map(cells => ({
+        cells,
+        value: evaluateMove(cells, state, options),
+        tieBreak: tieBreaker?.() ?? cells[0].col,
+      }))
+      .sort((a, b) => b.value - a.value || a.tieBreak - b.tieBreak)
+
+    const bestCombination = rankedCombinations[0].cells
+    const score = calculateChainedMoveScore(bestCombination, state.chain, options)
+
+    onGeneration?.(state.grid, combinations, bestCombination, rankedCombinations)
     
     // Apply the combination
-    const newGrid = currentGrid.map(row => [...row])
-    for (const cell of bestCombination) {
-      newGrid[cell.row][cell.col] = 0
-    }
+    state = playMove(state, bestCombination, options)
 
-    steps.push({
+    yield {
       cells: bestCombination,
-      sum: 10,
-      gridAfter: newGrid
-    })
+      sum: getCombinationSum(bestCombination),
+      score,
+      gridAfter: state.grid,
+      ...choices
+    }
+  }
+}
 
-    currentGrid = newGrid
+const runGreedy = (
+  initialGrid: number[][],
+  options: SolverOptions,
+  onGeneration?: GenerationCallback
+): Step[] => {
+  return Array.from(iterateGreedy(initialGrid, options, onGeneration))
+}
+
+// Solve only the smallest box containing every block, then map the steps back onto the full grid.
+// Gravity, wrapping and refills depend on the real board edges, and so do move rankings that look at the
+// board's size or shape (centrality, edges, styles, symmetry, a custom scoreMove), so those boards are
+// solved uncropped to pick the same moves.
+const solveCropped = (grid: number[][], options: SolverOptions): Step[] => {
+  const {
+    gravity = 'none',
+    wrapHorizontal = false,
+    wrapVertical = false,
+    playableMask,
+    frozenRows,
+    hiddenValues,
+    mustClear,
+    rowDividers,
+    colDividers,
+    refillQueue,
+  } = options
+  const blocks = grid.flatMap((row, r) => row.map((value, c) => ({ row: r, col: c, value }))).filter(c => c.value !== 0)
+  const dependsOnEdges = gravity !== 'none' || wrapHorizontal || wrapVertical || refillQueue
+  const ranksByBoardShape = options.centralityBonus || options.edgeFirstWeight || options.style ||
+    options.scoreMove || options.exploitSymmetry
+  if (blocks.length === 0 || dependsOnEdges || ranksByBoardShape) {
+    return runGreedy(grid, options)
   }
 
-  return steps
+  const { minRow, maxRow, minCol, maxCol } = getBoundingBox(blocks)
+  const crop = <T>(rows: T[][]) => rows.slice(minRow, maxRow + 1).map(row => row.slice(minCol, maxCol + 1))
+  const steps = runGreedy(crop(grid), {
+    ...options,
+    playableMask: playableMask && crop(playableMask),
+    frozenRows: frozenRows && Math.max(0, frozenRows - minRow),
+    hiddenValues: hiddenValues && crop(hiddenValues),
+    mustClear: mustClear?.map(([row, col]): [number, number] => [row - minRow, col - minCol]),
+    rowDividers: rowDividers?.map(d => d - minRow),
+    colDividers: colDividers?.map(d => d - minCol),
+  })
+
+  // Everything outside the box was empty to begin with, so it stays empty
+  return steps.map(step => ({
+    ...step,
+    cells: step.cells.map(c => ({ ...c, row: c.row + minRow, col: c.col + minCol })),
+    gridAfter: grid.map((row, r) => row.map((_, c) => step.gridAfter[r - minRow]?.[c - minCol] ?? 0)),
+  }))
 }
 
+// Translate 1-based coordinates in the options into the 0-based ones used internally
+export const toZeroIndexedOptions = (options: SolverOptions): SolverOptions => {
+  if (!options.oneIndexed) return options
+
+  const { preCleared, mustClear, rowDividers, colDividers } = options
+  const shift = (positions?: [number, number][]) => {
+    return positions?.map(([row, col]): [number, number] => [row - 1, col - 1])
+  }
+  return {
+    ...options,
+    oneIndexed: false,
+    preCleared: shift(preCleared),
+    mustClear: shift(mustClear),
+    rowDividers: rowDividers?.map(d => d - 1),
+    colDividers: colDividers?.map(d => d - 1),
+  }
+}
+
+// Solve the puzzle - find optimal sequence of moves
+export const solvePuzzle = (initialGrid: number[][], options: SolverOptions = {}): Step[] => {
+  const internalOptions = toZeroIndexedOptions(options)
+  const grid = prepareGrid(initialGrid, internalOptions)
+  const steps = options.autoCrop ? solveCropped(grid, internalOptions) : runGreedy(grid, internalOptions)
+  const ordered = options.reverse ? reverseSteps(grid, steps) : steps
+  return options.oneIndexed ? shiftSteps(ordered, 1) : ordered
+}
+
+// Solve the puzzle and return only the total score, without keeping any step or grid snapshot around
+export const solveScoreOnly = (initialGrid: number[][], options: SolverOptions = {}): number => {
+  const internalOptions = toZeroIndexedOptions(options)
+  let total = 0
+  for (const step of iterateGreedy(prepareGrid(initialGrid, internalOptions), internalOptions)) {
+    total += step.score
+  }
+  return total
+}
+
+// Solve the puzzle, handing each step to onStep as a JSON line as soon as it is chosen so no more than one
+// step is held at a time. Returns the number of steps written. The reverse option needs the whole solution
+// up front, so it is ignored here.
+export const streamSolution = (
+  initialGrid: number[][],
+  onStep: (line: string) => void,
+  options: SolverOptions = {}
+): number => {
+  const internalOptions = toZeroIndexedOptions(options)
+  let count = 0
+  for (const step of iterateGreedy(prepareGrid(initialGrid, internalOptions), internalOptions)) {
+    const [output] = options.oneIndexed ? shiftSteps([step], 1) : [step]
+    onStep(JSON.stringify(output))
+    count++
+  }
+  return count
+}
+
+// Solve the puzzle and return only the board snapshots: the starting grid followed by the grid after each move
+export const solveGridsOnly = (initialGrid: number[][], options: SolverOptions = {}): number[][][] => {
+  const internalOptions = toZeroIndexedOptions(options)
+  const grid = prepareGrid(initialGrid, internalOptions)
+  const grids = [grid]
+  for (const step of iterateGreedy(grid, internalOptions)) {
+    grids.push(step.gridAfter)
+  }
+  return options.reverse ? grids.reverse() : grids
+}
+
+// Re-run the solver with restarts seeds (seed, seed + 1, ...) breaking ties randomly, to show how much
+// the result depends on tie-breaking. Returns the score spread and the best run with its seed; among runs
+// with the best score, the one leaving the fewest blocks wins.
+export const solveWithRestarts = (
+  initialGrid: number[][],
+  restarts: number,
+  seed = 0,
+  options: SolverOptions = {}
+): RestartSummary => {
+  const grid = prepareGrid(initialGrid, toZeroIndexedOptions(options))
+  const runs = Array.from({ length: Math.max(1, restarts) }, (_, idx) => {
+    // Solve forwards so the last step holds the final board, and reverse only the winning run
+    const steps = solvePuzzle(initialGrid, { ...options, reverse: false, tieBreakSeed: seed + idx })
+    const remaining = countRemainingBlocks(steps[steps.length - 1]?.gridAfter ?? grid)
+    return { seed: seed + idx, steps, score: steps.reduce((acc, step) => acc + step.score, 0), remaining }
+  })
+  const best = runs.reduce((a, b) => (b.score > a.score || (b.score === a.score && b.remaining < a.remaining) ? b : a))
+  const scores = runs.map(run => run.score)
+
+  return {
+    minScore: Math.min(...scores),
+    meanScore: scores.reduce((acc, score) => acc + score, 0) / scores.length,
+    maxScore: best.score,
+    bestSeed: best.seed,
+    bestSteps: options.reverse ? reverseSteps(grid, best.steps) : best.steps,
+  }
+}
+
+// Solve the puzzle while timing how long it took to choose each move, in milliseconds
+export const solvePuzzleProfiled = (
+  initialGrid: number[][],
+  options: SolverOptions = {}
+): { steps: Step[]; stepTimesMs: number[] } => {
+  const internalOptions = toZeroIndexedOptions(options)
+  const grid = prepareGrid(initialGrid, internalOptions)
+  let steps: Step[] = []
+  let stepTimesMs: number[] = []
+  const iterator = iterateGreedy(grid, internalOptions)
+
+  while (true) {
+    const start = performance.now()
+    const { value, done } = iterator.next()
+    if (done) break
+
+    stepTimesMs.push(performance.now() - start)
+    steps.push(value)
+  }
+
+  if (options.reverse) {
+    steps = reverseSteps(grid, steps)
+    stepTimesMs = stepTimesMs.reverse()
+  }
+  return { steps: options.oneIndexed ? shiftSteps(steps, 1) : steps, stepTimesMs }
+}
+
+// Solve the puzzle while recording every explored state, capped at maxTreeNodes nodes.
+// Each move expands all candidates as children of the current node, and only the chosen one is explored further.
+export const solvePuzzleWithTree = (
+  initialGrid: number[][],
+  maxTreeNodes: number,
+  options: SolverOptions = {}
+): { steps: Step[]; tree: SearchTreeNode[] } => {
+  const internalOptions = toZeroIndexedOptions(options)
+  const offset = options.oneIndexed ? 1 : 0
+  const grid = prepareGrid(initialGrid, internalOptions)
+  const tree: SearchTreeNode[] = []
+  if (maxTreeNodes > 0) {
+    tree.push({ id: 0, parentId: null, cells: [], score: 0, grid })
+  }
+  let currentNodeId = 0
+
+  const steps = runGreedy(grid, internalOptions, (boardBefore, combinations, chosen) => {
+    const parent = tree[currentNodeId]
+    let chosenNodeId: number | null = null
+
+    for (const cells of combinations) {
+      if (tree.length >= maxTreeNodes) break
+
+      const childGrid = applyMove(boardBefore, cells, internalOptions)
+      const id = tree.length
+      tree.push({
+        id,
+        parentId: currentNodeId,
+        cells: shiftCells(cells, offset),
+        score: (parent?.score ?? 0) + calculateMoveScore(cells, internalOptions),
+        grid: childGrid,
+      })
+      if (cells === chosen) chosenNodeId = id
+    }
+
+    // Once the cap is hit the chosen node may be missing, so stop attaching further children
+    currentNodeId = chosenNodeId ?? maxTreeNodes
+  })
+
+  const ordered = options.reverse ? reverseSteps(grid, steps) : steps
+  return {
+    steps: shiftSteps(ordered, offset),
+    tree,
+  }
+}
+
+// Solve the puzzle while writing a text trace of each decision for debugging the move ranking:
+// one header line per move followed by its topK best-ranked candidates, the chosen one marked with '*'
+export const solvePuzzleWithTrace = (
+  initialGrid: number[][],
+  topK = 3,
+  options: SolverOptions = {}
+): { steps: Step[]; trace: string } => {
+  const internalOptions = toZeroIndexedOptions(options)
+  const offset = options.oneIndexed ? 1 : 0
+  const grid = prepareGrid(initialGrid, internalOptions)
+  const lines: string[] = []
+  let moveNumber = 0
+
+  const steps = runGreedy(grid, internalOptions, (_, combinations, chosen, ranked) => {
+    lines.push(`move ${++moveNumber} (${combinations.length} candidates)`)
+    for (const { cells, value } of ranked.slice(0, topK)) {
+      const marker = cells === chosen ? '*' : ' '
+      const positions = cells.map(c => `(${c.row + offset},${c.col + offset})`).join(' ')
+      lines.push(`  ${marker} ${value.toFixed(2)} ${positions}`)
+    }
+  })
+
+  const ordered = options.reverse ? reverseSteps(grid, steps) : steps
+  return {
+    steps: shiftSteps(ordered, offset),
+    trace: lines.join('\n'),
+  }
+}
+
+// Join a prefix solution with a suffix that was solved from the prefix's final board.
+// Returns null if the suffix didn't start from the board the prefix ends on.
+export const spliceSolutions = (
+  prefixStart: number[][],
+  prefix: Step[],
+  suffixStart: number[][],
+  suffix: Step[],
+  options: SolverOptions = {}
+): Step[] | null => {
+  const prefixEnd = prefix.length > 0 ? prefix[prefix.length - 1].gridAfter : prefixStart
+  if (getGridKey(prefixEnd) !== getGridKey(suffixStart)) return null
+
+  let chain = 0
+  return [...prefix, ...suffix].map(step => {
+    const score = calculateChainedMoveScore(step.cells, chain, options)
+    chain = getChainAfter(step.cells, chain, options)
+    return { ...step, score }
+  })
+}
+
+// Total score of a move followed by the solver's continuation from the game state it leads to
+const scoreAfterMove = (state: GameState, cells: Combination, options: SolverOptions): number => {
+  let total = calculateChainedMoveScore(cells, state.chain, options)
+  for (const step of iterateGreedyFrom(playMove(state, cells, options), options)) {
+    total += step.score
+  }
+  return total
+}
+
+// Pick the combination covering exactly the given [row, col] positions, if one is legal
+const findCombinationAt = (
+  combinations: Combination[],
+  positions: [number, number][],
+  options: SolverOptions
+): Combination | undefined => {
+  const offset = options.oneIndexed ? 1 : 0
+  const toKey = (pairs: number[][]) => pairs.map(([row, col]) => `${row}-${col}`).sort().join('|')
+
+  const targetKey = toKey(positions.map(([row, col]) => [row - offset, col - offset]))
+  return combinations.find(cells => toKey(cells.map(c => [c.row, c.col])) === targetKey)
+}
+
+// Rate a player's move by comparing what the solver reaches after it against the best first move, playing
+// by the same rules as the solver. Returns null if the given positions aren't a legal move on this board.
+export const getMoveQuality = (
+  grid: number[][],
+  positions: [number, number][],
+  options: SolverOptions = {}
+): MoveQuality | null => {
+  const internalOptions = toZeroIndexedOptions(options)
+  const start = startGame(grid, internalOptions)
+  const moves = getLegalMoves(start, internalOptions).filter(cells => meetsMinMoveScore(cells, start, internalOptions))
+  const move = findCombinationAt(moves, positions, options)
+  if (!move) return null
+
+  const moveScore = scoreAfterMove(start, move, internalOptions)
+  const bestScore = Math.max(...moves.map(cells => scoreAfterMove(start, cells, internalOptions)))
+
+  return { moveScore, bestScore, regret: bestScore - moveScore }
+}
+
+// Preview the moves the rules would allow after playing the move at the given positions.
+// Returns null if those positions aren't a legal move on this board.
+export const getMovesAfter = (
+  grid: number[][],
+  positions: [number, number][],
+  options: SolverOptions = {}
+): Combination[] | null => {
+  const internalOptions = toZeroIndexedOptions(options)
+  const start = startGame(grid, internalOptions)
+  const move = findCombinationAt(getLegalMoves(start, internalOptions), positions, options)
+  if (!move) return null
+
+  const combinations = getLegalMoves(playMove(start, move, internalOptions), internalOptions)
+  return options.oneIndexed ? combinations.map(cells => shiftCells(cells, 1)) : combinations
+}
+
+// Check whether a solution met the mustClear objective, i.e. every listed cell is empty on the final board
+export const isMustClearMet = (initialGrid: number[][], steps: Step[], options: SolverOptions = {}): boolean => {
+  const internalOptions = toZeroIndexedOptions(options)
+  const { mustClear = [] } = internalOptions
+  const finalGrid = steps.length > 0 ? steps[steps.length - 1].gridAfter : prepareGrid(initialGrid, internalOptions)
+  return mustClear.every(([row, col]) => (finalGrid[row]?.[col] ?? 0) === 0)
+}
+
+// Compare the moves available on two versions of a board (e.g. before and after an edit) and report
+// which rows, columns and rectangles gained or lost a combination. Nothing affected means no re-solve is needed.
+export const getAffectedLines = (
+  oldGrid: number[][],
+  newGrid: number[][],
+  options: SolverOptions = {}
+): AffectedLines => {
+  const offset = options.oneIndexed ? 1 : 0
+  const toKeyed = (grid: number[][]) => {
+    return new Map(enumerateCombinations(grid, toZeroIndexedOptions(options)).map(cells => {
+      const key = cells.map(c => `${c.row}-${c.col}-${c.value}`).sort().join('|')
+      return [key, cells]
+    }))
+  }
+  const before = toKeyed(oldGrid)
+  const after = toKeyed(newGrid)
+  const changed = [
+    ...[...before].filter(([key]) => !after.has(key)),
+    ...[...after].filter(([key]) => !before.has(key)),
+  ].map(([, cells]) => cells)
+
+  const rows = new Set<number>()
+  const cols = new Set<number>()
+  const rectangles: BoundingBox[] = []
+  for (const cells of changed) {
+    const shape = getSelectionType(cells)
+    if (shape === 'Rectangle') {
+      rectangles.push(getBoundingBox(shiftCells(cells, offset)))
+      continue
+    }
+    if (shape !== 'Vertical') rows.add(cells[0].row + offset)
+    if (shape !== 'Horizontal') cols.add(cells[0].col + offset)
+  }
+
+  return {
+    rows: [...rows].sort((a, b) => a - b),
+    cols: [...cols].sort((a, b) => a - b),
+    rectangles,
+  }
+}
+
+// Count the currently available combinations by size: index i holds how many clear exactly i blocks
+export const getComboSizeHistogram = (grid: number[][], options: SolverOptions = {}): number[] => {
+  const histogram: number[] = []
+  for (const cells of findValidCombinations(grid, options)) {
+    while (histogram.length <= cells.length) histogram.push(0)
+    histogram[cells.length]++
+  }
+  return histogram
+}
+
+// Quick sanity check of the configuration: can any group of the board's values add up to a combination
+// target at all, ignoring where the blocks sit? False means the board can never have a legal move.
+// Block values are whole numbers, so a fractional target can never be reached and is skipped.
+export const isTargetFeasible = (grid: number[][], options: SolverOptions = {}): boolean => {
+  const { horizontalTarget = 10, verticalTarget = 10, rectangleTarget = 10 } = options
+  const targets = [horizontalTarget, verticalTarget, rectangleTarget].filter(
+    target => Number.isInteger(target) && target > 0
+  )
+  if (targets.length === 0) return false
+
+  // Subset-sum over the block values, tracking every total up to the largest target
+  const maxTarget = Math.max(...targets)
+  const reachable = Array(maxTarget + 1).fill(false)
+  reachable[0] = true
+  for (const value of grid.flat()) {
+    if (value <= 0) continue
+    for (let total = maxTarget; total >= value; total--) {
+      if (reachable[total - value]) reachable[total] = true
+    }
+  }
+
+  return targets.some(target => reachable[target])
+}
+
+// A dead board still has blocks left but no legal combination to clear any of them. The board is checked
+// the way the solver starts on it, with the hypothetical edits and 1-based coordinates in the options applied.
+export const isDeadBoard = (initialGrid: number[][], options: SolverOptions = {}): boolean => {
+  const internalOptions = toZeroIndexedOptions(options)
+  const state = startGame(initialGrid, internalOptions)
+  return state.remaining > 0 && getLegalMoves(state, internalOptions).length === 0
+}
+
+// Roughly estimate how expensive solving a grid will be, based on the remaining blocks,
+// how densely packed they are and how many moves are available right away
+export const estimateCost = (grid: number[][], options: SolverOptions = {}): CostEstimate => {
+  const totalCells = grid.length * (grid[0]?.length || 0)
+  if (totalCells === 0) return 'fast'
+
+  const remaining = countRemainingBlocks(grid)
+  const density = remaining / totalCells
+  const branching = findValidCombinations(grid, options).length
+
+  // Every move re-enumerates the board, so the work grows with both the blocks left and the choices per move
+  const work = remaining * Math.max(1, branching) * (0.5 + density)
+
+  if (work < 1000) return 'fast'
+  if (work < 20000) return 'medium'
+  return 'slow'
+}
+
+// Collect the board analyses useful when triaging a slow solve into a single report
+export const diagnose = (grid: number[][], options: SolverOptions = {}): Diagnostics => {
+  const combinations = findValidCombinations(grid, options)
+  const rectangles = combinations.filter(cells => getSelectionType(cells) === 'Rectangle').length
+  const totalCells = grid.length * (grid[0]?.length || 0)
+
+  return {
+    branchingFactor: combinations.length,
+    rectangleShare: combinations.length > 0 ? rectangles / combinations.length : 0,
+    rectanglesDominate: rectangles > combinations.length - rectangles,
+    density: totalCells > 0 ? countRemainingBlocks(grid) / totalCells : 0,
+    symmetry: getBoardSymmetry(grid),
+    cost: estimateCost(grid, options),
+  }
+}
@@ -0,0 +1,48 @@
+import type { SolverOptions, Step } from '../types'
+import { createRng } from './utils'
+
+// Seeded board with a block in most cells, so the solver has plenty of moves to play
+export const randomBoard = (height: number, width: number, seed: number): number[][] => {
+  const random = createRng(seed)
+  return Array.from({ length: height }, () => {
+    return Array.from({ length: width }, () => (random() < 0.85 ? 1 + Math.floor(random() * 9) : 0))
+  })
+}
+
+export const totalScore = (steps: Step[]) => steps.reduce((acc, step) => acc + step.score, 0)
+
+// One named option set per rule that changes which moves are legal, with positions that fit the board
+export const getRuleCases = (grid: number[][]): [string, SolverOptions][] => {
+  const height = grid.length
+  const width = grid[0]?.length ?? 0
+  const midRow = Math.floor(height / 2)
+  const midCol = Math.floor(width / 2)
+
+  return [
+    ['shapeSchedule', { shapeSchedule: ['Horizontal', 'Vertical'] }],
+    ['requireValue per move', { requireValue: [5, null, 1] }],
+    ['preCleared', { preCleared: [[0, 0], [midRow, midCol], [height - 1, width - 1]] }],
+    ['consecutiveOverlap forbid', { consecutiveOverlap: 'forbid' }],
+    ['forbidRepeatShape', { forbidRepeatShape: true }],
+    ['clearOrder', { clearOrder: 'bottomUp' }],
+    ['budget', { moveCost: 3, clearReward: 1, startingBudget: 1 }],
+    ['maxTotalCleared', { maxTotalCleared: Math.floor((height * width) / 4) }],
+    ['gravity down', { gravity: 'down' }],
+    ['gravity left', { gravity: 'left' }],
+    ['refillQueue', { gravity: 'down', refillQueue: [5, 5, 1, 9, 3, 7, 2, 8] }],
+    ['chainBonus', { chainBonus: 1, chainMinSize: 3 }],
+    ['scoreSingleCellClears', { scoreSingleCellClears: false }],
+    ['minMoveScore', { minMoveScore: 3 }],
+    ['maxGaps', { maxGaps: 1 }],
+    ['wrap', { wrapHorizontal: true, wrapVertical: true }],
+    ['solid rectangles', { rectangleMode: 'solid' }],
+    ['dividers', { rowDividers: [midRow], colDividers: [midCol] }],
+    ['frozenRows', { frozenRows: Math.floor(height / 3) }],
+    ['playableMask', { playableMask: grid.map((row, r) => row.map((_, c) => r < midRow || c < midCol)) }],
+    ['valueConstraint', { valueConstraint: { odd: { max: 2 } } }],
+    ['mustClear', { mustClear: [[height - 1, width - 1]] }],
+    ['exploitSymmetry', { exploitSymmetry: true }],
+    ['autoCrop', { autoCrop: true, preCleared: grid[0]?.map((_, c): [number, number] => [0, c]) ?? [] }],
+    ['oneIndexed', { oneIndexed: true, preCleared: [[1, 1]], mustClear: [[height, width]] }],
+  ]
+}
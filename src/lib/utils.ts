@@ -1,12 +1,50 @@
-import type { Cell } from '../types'
+import type {
+  BoundingBox,
+  Cell,
+  Combination,
+  CountRange,
+  GravityRule,
+  SelectionType,
+  Step,
+  ValueConstraint,
+} from '../types'
+
+// Marks a cell whose value stays unknown until a neighbouring block is cleared
+export const HIDDEN_CELL = -1
 
 // Count remaining blocks in a grid
 export const countRemainingBlocks = (grid: number[][]): number => {
   return grid.flat().filter(v => v > 0).length
 }
 
+// Total of all block values and whether it divides evenly by the target, which a full clear requires
+export const getBoardSumInfo = (grid: number[][], target = 10): { sum: number; divisible: boolean } => {
+  const sum = grid.flat().filter(v => v > 0).reduce((acc, v) => acc + v, 0)
+  return { sum, divisible: target !== 0 && sum % target === 0 }
+}
+
+// Shannon entropy (in bits) of the value distribution among remaining blocks.
+// Low entropy means most blocks share the same value, which makes for dull puzzles.
+export const getBoardEntropy = (grid: number[][]): number => {
+  const values = grid.flat().filter(v => v > 0)
+  if (values.length === 0) return 0
+
+  const counts = new Map<number, number>()
+  for (const value of values) {
+    counts.set(value, (counts.get(value) ?? 0) + 1)
+  }
+
+  let entropy = 0
+  for (const count of counts.values()) {
+    const p = count / values.length
+    entropy -= p * Math.log2(p)
+  }
+  return entropy
+}
+
 // Get selection type description based on cells
-export const getSelectionType = (cells: Cell[]): 'Single' | 'Horizontal' | 'Vertical' | 'Rectangle' => {
+export const getSelectionType = (cells: Cell[]): SelectionType => {
+  if (cells.length === 0) return 'Pass'
   if (cells.length === 1) return 'Single'
   
   const rows = [...new Set(cells.map(c => c.row))]
@@ -17,7 +55,220 @@ export const getSelectionType = (cells: Cell[]): 'Single' | 'Horizontal' | 'Vert
   return 'Rectangle'
 }
 
+// Total value of the cells in a combination
+export const getCombinationSum = (cells: Combination): number => {
+  return cells.reduce((acc, cell) => acc + cell.value, 0)
+}
+
+// Check a combination's value composition against a constraint
+export const matchesValueConstraint = (cells: Combination, constraint: ValueConstraint): boolean => {
+  const inRange = (count: number, range?: CountRange) => {
+    return !range || (count >= (range.min ?? 0) && count <= (range.max ?? Infinity))
+  }
+  const countWhere = (predicate: (value: number) => boolean) => cells.filter(c => predicate(c.value)).length
+
+  return (
+    inRange(countWhere(value => value % 2 === 0), constraint.even) &&
+    inRange(countWhere(value => value % 2 !== 0), constraint.odd) &&
+    Object.entries(constraint.perValue ?? {}).every(([value, range]) => {
+      return inRange(countWhere(v => v === Number(value)), range)
+    })
+  )
+}
+
+// Row/column positions of a combination, sorted top-to-bottom then left-to-right
+export const getCombinationPositions = (cells: Combination): [number, number][] => {
+  return cells
+    .map(c => [c.row, c.col] as [number, number])
+    .sort((a, b) => a[0] - b[0] || a[1] - b[1])
+}
+
+// Smallest box containing every cell of a combination
+export const getBoundingBox = (cells: Combination): BoundingBox => {
+  return {
+    minRow: Math.min(...cells.map(c => c.row)),
+    maxRow: Math.max(...cells.map(c => c.row)),
+    minCol: Math.min(...cells.map(c => c.col)),
+    maxCol: Math.max(...cells.map(c => c.col)),
+  }
+}
+
+// Check whether two bounding boxes share at least one cell
+export const boundingBoxesOverlap = (a: BoundingBox, b: BoundingBox): boolean => {
+  return a.minRow <= b.maxRow && b.minRow <= a.maxRow && a.minCol <= b.maxCol && b.minCol <= a.maxCol
+}
+
+// Smallest box containing both boxes
+export const mergeBoundingBoxes = (a: BoundingBox, b: BoundingBox): BoundingBox => {
+  return {
+    minRow: Math.min(a.minRow, b.minRow),
+    maxRow: Math.max(a.maxRow, b.maxRow),
+    minCol: Math.min(a.minCol, b.minCol),
+    maxCol: Math.max(a.maxCol, b.maxCol),
+  }
+}
+
+// Number of cells covered by a bounding box
+export const getBoundingBoxArea = (box: BoundingBox): number => {
+  return (box.maxRow - box.minRow + 1) * (box.maxCol - box.minCol + 1)
+}
+
+// Area of the smallest box containing every cell cleared by a solution, 0 if it clears nothing
+export const getSolutionActivityArea = (steps: Step[]): number => {
+  const cells = steps.flatMap(step => step.cells)
+  return cells.length > 0 ? getBoundingBoxArea(getBoundingBox(cells)) : 0
+}
+
+// Tally cleared cells into a coarse regionRows x regionCols overlay of a height x width board, for heatmaps
+export const getRegionActivity = (
+  steps: Step[],
+  height: number,
+  width: number,
+  regionRows = 3,
+  regionCols = 3
+): number[][] => {
+  const tally = createEmptyGrid(regionRows, regionCols)
+  for (const cell of steps.flatMap(step => step.cells)) {
+    const regionRow = Math.min(regionRows - 1, Math.floor((cell.row * regionRows) / height))
+    const regionCol = Math.min(regionCols - 1, Math.floor((cell.col * regionCols) / width))
+    tally[regionRow][regionCol]++
+  }
+  return tally
+}
+
+// Total cells cleared in each row and each column across a whole solution
+export const getClearDistribution = (
+  steps: Step[],
+  height: number,
+  width: number
+): { rows: number[]; cols: number[] } => {
+  const rows = Array(height).fill(0)
+  const cols = Array(width).fill(0)
+  for (const cell of steps.flatMap(step => step.cells)) {
+    rows[cell.row]++
+    cols[cell.col]++
+  }
+  return { rows, cols }
+}
+
+// Which mirror symmetries a board has: leftRight when each row reads the same backwards,
+// topBottom when the rows read the same in reverse order
+export const getBoardSymmetry = (grid: number[][]): { leftRight: boolean; topBottom: boolean } => {
+  return {
+    leftRight: grid.every(row => row.every((value, col) => value === row[row.length - 1 - col])),
+    topBottom: grid.every((row, r) => getGridKey([row]) === getGridKey([grid[grid.length - 1 - r]])),
+  }
+}
+
+// Lines blocks slide along under gravity, each listed in the order blocks settle: every column bottom to top
+// ('down') or every row left to right ('left'). Fixed cells split a line into separate lanes, since they hold
+// their place and hold up the blocks beyond them.
+export const getGravityLanes = (
+  height: number,
+  width: number,
+  gravity: GravityRule,
+  isFixed: (row: number, col: number) => boolean = () => false
+): [number, number][][] => {
+  if (gravity === 'none') return []
+
+  const lines: [number, number][][] = gravity === 'down'
+    ? Array.from({ length: width }, (_, col) => Array.from({ length: height }, (_, idx) => [height - 1 - idx, col]))
+    : Array.from({ length: height }, (_, row) => Array.from({ length: width }, (_, col) => [row, col]))
+
+  const lanes: [number, number][][] = []
+  for (const line of lines) {
+    let lane: [number, number][] = []
+    for (const [row, col] of line) {
+      if (isFixed(row, col)) {
+        if (lane.length > 0) lanes.push(lane)
+        lane = []
+      } else {
+        lane.push([row, col])
+      }
+    }
+    if (lane.length > 0) lanes.push(lane)
+  }
+  return lanes
+}
+
+// Compact remaining blocks towards the bottom of each column or the left of each row.
+// Fixed cells (e.g. walls) stay where they are and blocks only slide within the lanes between them.
+export const applyGravity = (
+  grid: number[][],
+  gravity: GravityRule,
+  isFixed: (row: number, col: number) => boolean = () => false
+): number[][] => {
+  if (gravity === 'none') return grid
+
+  const newGrid = grid.map(row => [...row])
+  for (const lane of getGravityLanes(grid.length, grid[0]?.length ?? 0, gravity, isFixed)) {
+    const blocks = lane.map(([row, col]) => grid[row][col]).filter(value => value !== 0)
+    lane.forEach(([row, col], idx) => {
+      newGrid[row][col] = blocks[idx] ?? 0
+    })
+  }
+  return newGrid
+}
+
+// Compact string identifying a board state, used to compare grids cheaply
+export const getGridKey = (grid: number[][]): string => {
+  return grid.map(row => row.join(',')).join('|')
+}
+
+// Check that a grid is rectangular and only holds block values 0-9 (plus hidden cells when allowed),
+// returning an error message if not
+export const validateGrid = (grid: unknown, allowHidden = false): string | null => {
+  // Catch the most common mistake (a scalar, object or flat array) before inspecting the values
+  if (!Array.isArray(grid) || !grid.every(row => Array.isArray(row))) {
+    return 'Expected a 2D array of numbers'
+  }
+
+  const width = grid[0]?.length ?? 0
+
+  for (const row of grid) {
+    if (row.length !== width) return 'All rows must have the same length'
+    for (const cell of row) {
+      if (allowHidden && cell === HIDDEN_CELL) continue
+      if (typeof cell !== 'number' || !Number.isInteger(cell) || cell < 0 || cell > 9) {
+        return 'Block values must be whole numbers from 0 to 9'
+      }
+    }
+  }
+
+  return null
+}
+
+// Small seeded pseudo-random generator (mulberry32) returning floats in [0, 1), for reproducible boards
+export const createRng = (seed: number): (() => number) => {
+  let state = seed >>> 0
+  return () => {
+    state = (state + 0x6d2b79f5) >>> 0
+    let t = state
+    t = Math.imul(t ^ (t >>> 15), t | 1)
+    t ^= t + Math.imul(t ^ (t >>> 7), t | 61)
+    return ((t ^ (t >>> 14)) >>> 0) / 4294967296
+  }
+}
+
 // Create an empty grid with specified dimensions
 export const createEmptyGrid = (height: number, width: number): number[][] => {
   return Array(height).fill(null).map(() => Array(width).fill(0))
 }
+
+// Reverse a solution so that replaying it from the final board rebuilds the initial grid
+export const reverseSteps = (initialGrid: number[][], steps: Step[]): Step[] => {
+  return steps.map((step, idx) => ({
+    ...step,
+    gridAfter: idx === 0 ? initialGrid.map(row => [...row]) : steps[idx - 1].gridAfter,
+  })).reverse()
+}
+
+// Move every cell of a combination by delta rows and columns, e.g. to convert between 0- and 1-based coordinates
+export const shiftCells = (cells: Combination, delta: number): Combination => {
+  return cells.map(c => ({ ...c, row: c.row + delta, col: c.col + delta }))
+}
+
+// Move every cell of a solution by delta rows and columns
+export const shiftSteps = (steps: Step[], delta: number): Step[] => {
+  return steps.map(step => ({ ...step, cells: shiftCells(step.cells, delta) }))
+}
@@ -0,0 +1,90 @@
+import { describe, expect, it } from 'vitest'
+import type { SolverOptions } from '../types'
+import { solvePuzzle } from './solver'
+import { getRuleCases, randomBoard, totalScore } from './testHelpers'
+import { getFinalBoard, verifyBatch, verifySolution } from './verify'
+
+describe('verifySolution', () => {
+  it('accepts the solver output and recomputes its score', () => {
+    for (let seed = 1; seed <= 20; seed++) {
+      const grid = randomBoard(6, 7, seed)
+      const steps = solvePuzzle(grid)
+      expect(verifySolution(grid, steps)).toEqual({ valid: true, score: totalScore(steps) })
+    }
+  })
+
+  it('rejects a move that is not legal on the current board', () => {
+    const grid = [[1, 9, 5, 5]]
+    const result = verifySolution(grid, [
+      { cells: [{ row: 0, col: 0, value: 1 }, { row: 0, col: 2, value: 5 }], sum: 6, score: 2, gridAfter: grid },
+    ])
+    expect(result).toMatchObject({ valid: false, stepIndex: 0 })
+  })
+
+  it('rejects replaying the same move twice', () => {
+    const grid = [[1, 9, 5, 5]]
+    const [first] = solvePuzzle(grid)
+    expect(verifySolution(grid, [first, first])).toMatchObject({ valid: false, stepIndex: 1 })
+  })
+
+  it('reports malformed grids instead of throwing', () => {
+    expect(verifySolution([[1, 2], [3]], [])).toMatchObject({ valid: false })
+  })
+})
+
+describe('verifyBatch', () => {
+  it('checks each submission against its own grid', () => {
+    const grids = [randomBoard(4, 4, 1), randomBoard(4, 4, 2)]
+    const good = solvePuzzle(grids[0])
+    const results = verifyBatch(grids, [good, good])
+
+    expect(results[0]).toEqual({ valid: true, score: totalScore(good) })
+    expect(results[1].valid).toBe(false)
+  })
+})
+
+describe('getFinalBoard', () => {
+  it('ends on the board of the last step', () => {
+    const grid = randomBoard(5, 5, 3)
+    const steps = solvePuzzle(grid)
+    expect(steps.length).toBeGreaterThan(0)
+    expect(getFinalBoard(grid, steps)).toEqual(steps[steps.length - 1].gridAfter)
+  })
+
+  it('returns null for a solution with an illegal move', () => {
+    const grid = [[1, 9, 5, 5]]
+    const [first] = solvePuzzle(grid)
+    expect(getFinalBoard(grid, [first, first])).toBeNull()
+  })
+})
+
+describe('solve and verify agree on the rules', () => {
+  const grid = randomBoard(6, 6, 11)
+  it.each(getRuleCases(grid))('accepts the solver output with %s', (_, options) => {
+    const steps = solvePuzzle(grid, options)
+    expect(verifySolution(grid, steps, options)).toEqual({ valid: true, score: totalScore(steps) })
+  })
+
+  it('accepts the solver output with hidden cells', () => {
+    const hidden = grid.map((row, r) => row.map((value, c) => ((r + c) % 4 === 0 ? -1 : value)))
+    const options: SolverOptions = { hiddenValues: grid }
+    const steps = solvePuzzle(hidden, options)
+    expect(verifySolution(hidden, steps, options)).toEqual({ valid: true, score: totalScore(steps) })
+  })
+
+  it('rejects a move breaking a move-to-move rule', () => {
+    const board = [[1, 9, 2, 8]]
+    const [first, second] = solvePuzzle(board)
+    expect(verifySolution(board, [first, second])).toMatchObject({ valid: true })
+    expect(verifySolution(board, [first, second], { forbidRepeatShape: true })).toMatchObject({
+      valid: false,
+      stepIndex: 1,
+    })
+  })
+
+  it('replays from the board with preCleared applied', () => {
+    const board = [[1, 9, 5, 5]]
+    const steps = solvePuzzle([[1, 9, 5, 5]])
+    expect(verifySolution(board, steps, { preCleared: [[0, 0]] }).valid).toBe(false)
+  })
+})
@@ -0,0 +1,84 @@
+import type { SolverOptions, Step, VerifyResult } from '../types'
+import {
+  calculateChainedMoveScore,
+  getLegalMoves,
+  meetsMinMoveScore,
+  playMove,
+  startGame,
+  toZeroIndexedOptions,
+} from './solver'
+import { shiftSteps, validateGrid } from './utils'
+
+// Overwrite a reusable buffer with a copy of the grid, keeping its row arrays where possible
+const loadGrid = (buffer: number[][], grid: number[][]) => {
+  buffer.length = grid.length
+  grid.forEach((row, r) => {
+    buffer[r] ??= []
+    buffer[r].length = row.length
+    row.forEach((value, c) => {
+      buffer[r][c] = value
+    })
+  })
+}
+
+// Replay a solution under the same per-move rules the solver plays by, recomputing the score instead of
+// trusting the submission, and leave the final board in the buffer
+const replaySolution = (
+  buffer: number[][],
+  grid: number[][],
+  steps: Step[],
+  options: SolverOptions
+): VerifyResult => {
+  const error = validateGrid(grid, options.hiddenValues !== undefined)
+  if (error) return { valid: false, error }
+
+  const internalOptions = toZeroIndexedOptions(options)
+  const moves = options.oneIndexed ? shiftSteps(steps, -1) : steps
+  let state = startGame(grid, internalOptions)
+  let score = 0
+
+  for (const [stepIndex, step] of moves.entries()) {
+    // A pass ends the game, so nothing may follow it
+    if (step.cells.length === 0) {
+      if (stepIndex !== moves.length - 1) return { valid: false, error: 'Moves cannot follow a pass', stepIndex }
+      continue
+    }
+
+    const key = step.cells.map(c => `${c.row}-${c.col}`).sort().join('|')
+    const move = getLegalMoves(state, internalOptions)
+      .find(cells => cells.map(c => `${c.row}-${c.col}`).sort().join('|') === key)
+    if (!move) return { valid: false, error: 'Not a legal move at this point of the game', stepIndex }
+    if (!meetsMinMoveScore(move, state, internalOptions)) {
+      return { valid: false, error: 'Move scores below minMoveScore', stepIndex }
+    }
+
+    score += calculateChainedMoveScore(move, state.chain, internalOptions)
+    state = playMove(state, move, internalOptions)
+  }
+
+  loadGrid(buffer, state.grid)
+  return { valid: true, score }
+}
+
+// Check that a solution only plays legal moves from the given grid, returning the score it earns
+export const verifySolution = (grid: number[][], steps: Step[], options: SolverOptions = {}): VerifyResult => {
+  return replaySolution([], grid, steps, options)
+}
+
+// Verify many submissions at once, writing their final boards into a single shared buffer.
+// Grids without a matching solution are checked against an empty solution.
+export const verifyBatch = (
+  grids: number[][][],
+  solutions: Step[][],
+  options: SolverOptions = {}
+): VerifyResult[] => {
+  const buffer: number[][] = []
+  return grids.map((grid, idx) => replaySolution(buffer, grid, solutions[idx] ?? [], options))
+}
+
+// Replay a solution and return only the board it ends on, or null if it plays an illegal move
+// (verifySolution reports which step and why)
+export const getFinalBoard = (grid: number[][], steps: Step[], options: SolverOptions = {}): number[][] | null => {
+  const buffer: number[][] = []
+  return replaySolution(buffer, grid, steps, options).valid ? buffer : null
+}
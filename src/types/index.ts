@@ -4,8 +4,311 @@ export interface Cell {
   value: number
 }
 
+// A set of cells cleared together in one move
+export type Combination = Cell[]
+
+export type SelectionType = 'Pass' | 'Single' | 'Horizontal' | 'Vertical' | 'Rectangle'
+
+export type ComboShape = Exclude<SelectionType, 'Pass'>
+
+export interface BoundingBox {
+  minRow: number
+  maxRow: number
+  minCol: number
+  maxCol: number
+}
+
+// A pass step has no cells and leaves the grid unchanged
 export interface Step {
   cells: Cell[]
   sum: number
+  score: number
   gridAfter: number[][]
+  // Number of legal combinations available before this move (only with recordChoices)
+  choicesBefore?: number
+}
+
+// A game in progress: the board plus everything earlier moves carry over into the rules for the next one
+export interface GameState {
+  grid: number[][]
+  // Moves played so far
+  moveIndex: number
+  previous?: Combination
+  // Resource left to pay for moves
+  budget: number
+  // Smallest box containing every cell cleared so far
+  activity?: BoundingBox
+  // Consecutive large clears played right before this move
+  chain: number
+  // Blocks cleared by every move so far
+  cleared: number
+  // Blocks left on the board, updated move by move (clears, reveals and refills) rather than recounted
+  remaining: number
+  // Values taken from the refill queue so far
+  refillUsed: number
+}
+
+// Step whose board is stored in full only on keyframes; other steps list the cells that changed
+// since the previous step as [row, col, newValue]
+export interface KeyframedStep {
+  cells: Cell[]
+  sum: number
+  score: number
+  gridAfter?: number[][]
+  changes?: [number, number, number][]
+}
+
+// Flat view of a move for the UI: [row, col, value] tuples sorted by position
+export interface CompactMove {
+  cells: [number, number, number][]
+  shape: SelectionType
+  score: number
+}
+
+// One step of an animation script: blocks to highlight as the move, positions that end up empty
+// once the move (and any gravity) resolves, and the board to show afterwards
+export interface AnimationFrame {
+  highlight: Cell[]
+  removed: [number, number][]
+  board: number[][]
+}
+
+// Everything needed to reproduce a solve exactly, including any tie-breaking seed, in a JSON-safe form
+export interface SolveRecipe {
+  // Board encoded with gridToBase64
+  grid: string
+  options: SolverOptions
+}
+
+// A (board, move) pair for training move prediction: the row-major board before the step and the index
+// of the chosen move in getLegalMoves for that game state (-1 for a pass)
+export interface TrainingSample {
+  board: number[]
+  moveIndex: number
+}
+
+export interface SearchTreeNode {
+  id: number
+  parentId: number | null
+  cells: Cell[]
+  score: number
+  grid: number[][]
+}
+
+// Structure-of-arrays view of a solution: entry i of each per-step array describes step i,
+// and the cells of step i are cellRows/cellCols/cellValues[cellOffsets[i] .. cellOffsets[i + 1])
+export interface ColumnarLog {
+  startRow: number[]
+  startCol: number[]
+  size: number[]
+  score: number[]
+  sum: number[]
+  shape: SelectionType[]
+  cellOffsets: number[]
+  cellRows: number[]
+  cellCols: number[]
+  cellValues: number[]
+}
+
+export interface MoveQuality {
+  // Best score reachable after playing the given move
+  moveScore: number
+  // Best score reachable from the board with any first move
+  bestScore: number
+  // How much the given move gives up compared to the best first move
+  regret: number
+}
+
+// Where the available combinations differ between two boards
+export interface AffectedLines {
+  rows: number[]
+  cols: number[]
+  rectangles: BoundingBox[]
+}
+
+// A solution on the score vs. move count trade-off frontier
+export interface ParetoSolution {
+  score: number
+  moves: number
+  steps: Step[]
+}
+
+// Solvers that solveBestOf runs and compares
+export type SolveMethod = 'greedy' | 'exhaustive'
+
+// The better of several solvers' solutions and which solver found it
+export interface BestOfSolution {
+  steps: Step[]
+  method: SolveMethod
+  // Solvers that gave up before finishing, so their solution was not compared
+  partialMethods: SolveMethod[]
+}
+
+// One solver's solution along with the score it earns
+export interface MethodSolution {
+  steps: Step[]
+  score: number
+}
+
+// Every solver's solution side by side; null for a solver that gave up before finishing
+export type SolveAllResult = Record<SolveMethod, MethodSolution | null>
+
+// Score spread over several seeded solves, with the best run
+export interface RestartSummary {
+  minScore: number
+  meanScore: number
+  maxScore: number
+  bestSeed: number
+  bestSteps: Step[]
+}
+
+// A line of blocks whose sum is delta away from the target
+export interface NearMiss {
+  cells: Combination
+  delta: number
+}
+
+// Outcome of replaying a submitted solution; stepIndex points at the first illegal step
+export type VerifyResult =
+  | { valid: true; score: number }
+  | { valid: false; error: string; stepIndex?: number }
+
+// 'sparse' rectangles skip empty cells inside the box, 'solid' ones require every cell to be filled
+export type RectangleMode = 'solid' | 'sparse'
+
+// 'score' clears as many blocks per move as possible, 'maxMoves' plays as many moves as possible.
+// 'maxSingleClear' goes for the biggest single clear the board allows, then the best score; only the
+// exhaustive search can plan for it, the greedy solver plays it like 'score'.
+export type SolverObjective = 'score' | 'maxMoves' | 'maxSingleClear'
+
+export type CostEstimate = 'fast' | 'medium' | 'slow'
+
+// Summary of what makes a board expensive to solve
+export interface Diagnostics {
+  // Legal moves available on the initial board
+  branchingFactor: number
+  // Share of those moves that are rectangles, and whether they outnumber lines
+  rectangleShare: number
+  rectanglesDominate: boolean
+  // Fraction of cells holding a block
+  density: number
+  symmetry: { leftRight: boolean; topBottom: boolean }
+  cost: CostEstimate
+}
+
+// How consecutive moves with overlapping bounding boxes are treated
+export type OverlapRule = 'allow' | 'penalize' | 'forbid'
+
+// Direction remaining blocks fall after each clear
+export type GravityRule = 'none' | 'down' | 'left'
+
+// Inclusive bounds on how many cells of a combination match some property
+export interface CountRange {
+  min?: number
+  max?: number
+}
+
+// Rules on the values making up a combination: how many even and odd blocks it may hold,
+// and how many blocks of specific values (e.g. { 5: { max: 1 } })
+export interface ValueConstraint {
+  even?: CountRange
+  odd?: CountRange
+  perValue?: Record<number, CountRange>
+}
+
+// Order rows must be cleared in: 'bottomUp' only clears a row once everything below it is gone,
+// 'topDown' once everything above it is gone
+export type ClearOrder = 'any' | 'bottomUp' | 'topDown'
+
+// Named bundles of move-ranking weights
+export type SolverStyle = 'aggressive' | 'efficient' | 'balanced'
+
+export interface SolverOptions {
+  rectangleMode?: RectangleMode
+  objective?: SolverObjective
+  reverse?: boolean
+  // Time slice in milliseconds for finding each move's combinations. Once it runs out the scan keeps what it
+  // found, but it never gives up before finding at least one move.
+  perGenerationBudgetMs?: number
+  // Only allow combinations containing this value, or one value per move (null leaves that move unrestricted)
+  requireValue?: number | (number | null)[]
+  // Number of unselected blocks a line combination may skip over between its first and last cell
+  maxGaps?: number
+  consecutiveOverlap?: OverlapRule
+  // Pass and stop instead of playing a move scoring below this
+  minMoveScore?: number
+  gravity?: GravityRule
+  // Extra priority for moves near the board center, divided by (1 + distance from the center)
+  centralityBonus?: number
+  // Extra priority per board side touched by a cleared block
+  edgeFirstWeight?: number
+  // [row, col] positions treated as already cleared, applied to a copy of the grid
+  preCleared?: [number, number][]
+  recordChoices?: boolean
+  // Move k may only use the shape shapeSchedule[k % shapeSchedule.length]
+  shapeSchedule?: ComboShape[]
+  // When false, clearing a single block is a free move worth no points
+  scoreSingleCellClears?: boolean
+  style?: SolverStyle
+  // Cells marked false are walls: never selectable and blocking lines and rectangles
+  playableMask?: boolean[][]
+  // Let row runs continue from the last column to the first, and column runs from the last row to the first
+  wrapHorizontal?: boolean
+  wrapVertical?: boolean
+  // Solve only the region between the outermost blocks, mapping coordinates back afterwards
+  autoCrop?: boolean
+  // [row, col] cells the solution has to clear; moves that would leave one unreachable are skipped
+  mustClear?: [number, number][]
+  // Split-board lines no combination may cross; a divider at index d runs just before row/column d
+  rowDividers?: number[]
+  colDividers?: number[]
+  // Resource economy: every move costs moveCost, every cleared block pays clearReward,
+  // and moves that would take the budget below zero are never played
+  moveCost?: number
+  clearReward?: number
+  startingBudget?: number
+  // Most blocks the whole solution may clear; moves that would go past it are never played
+  maxTotalCleared?: number
+  // Break ties between equally ranked moves randomly from this seed instead of picking the leftmost
+  tieBreakSeed?: number
+  // Sum each shape of combination has to reach (10 by default); a step's sum records the target it matched
+  horizontalTarget?: number
+  verticalTarget?: number
+  rectangleTarget?: number
+  // Custom move rating used for ranking instead of the objective; reported step scores are unchanged.
+  // It runs once per candidate on every move, so keep it cheap. Functions can't be posted to a worker,
+  // so this only works when calling the solver directly.
+  scoreMove?: (cells: Combination, grid: number[][]) => number
+  // Chain scoring: each clear of at least chainMinSize (default 4) blocks following an unbroken run of
+  // such clears scores an extra chainBonus times its base score per clear in the run
+  chainBonus?: number
+  chainMinSize?: number
+  clearOrder?: ClearOrder
+  // On a mirror-symmetric board, drop the mirrored twin of each first move since both lead to equivalent games.
+  // Only block values are compared, so leave it off when masks, dividers or frozen rows break the symmetry.
+  exploitSymmetry?: boolean
+  // Count mirror-image solutions as one in countOptimalSolutions
+  collapseSymmetricSolutions?: boolean
+  // Stop enumerating combinations for a move once this many are found
+  maxCombinations?: number
+  // Ranking penalty for rectangular moves, favoring lines (a negative one favors rectangles);
+  // reported step scores are unchanged
+  rectanglePenalty?: number
+  // Penalty per cell a move adds to the bounding box of everything cleared so far, favoring compact solutions
+  compactnessWeight?: number
+  // Number of rows at the top of the board that are frozen: their blocks can never be cleared and block selections
+  frozenRows?: number
+  // Only allow combinations whose values satisfy these counts
+  valueConstraint?: ValueConstraint
+  // Real values behind hidden cells (-1 in the grid), revealed once an adjacent block is cleared.
+  // Hidden cells block selections until then.
+  hiddenValues?: number[][]
+  // Never play two moves of the same shape in a row
+  forbidRepeatShape?: boolean
+  // Endless mode: after each clear (and gravity), every cleared block is replaced by the next value
+  // from this queue until it runs out. Refilled blocks stack where gravity left space.
+  refillQueue?: number[]
+  // Use 1-based row/column numbers for the coordinates taken and returned by the solve,
+  // move quality and verify functions (debug trees and traces stay 0-based)
+  oneIndexed?: boolean
 }